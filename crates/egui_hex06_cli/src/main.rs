@@ -0,0 +1,170 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+//! Headless command line interface: works on files without opening a window.
+
+use std::{path::PathBuf, process::ExitCode};
+
+const USAGE: &str = "\
+Usage:
+  egui_hex06_cli --render OUT.png [OPTIONS] FILE0 [FILE1]
+  egui_hex06_cli --diff-report [OPTIONS] FILE0 FILE1
+
+Options:
+  --level N            range block recursion level to draw or report
+                       (default: 0 when rendering; 2 below the maximum level for diff reports,
+                       which list at most 256 blocks)
+
+Render options:
+  --block-pixels N     width and height of each drawn range block, in pixels (default: 1)
+  --color-mode MODE    value, semantic01, or diff (diff compares FILE0 to FILE1) (default: value)
+
+Diff report options:
+  Prints a JSON report to stdout.
+  --threshold N        differing bytes allowed before exiting with status 1 (default: 0)
+                       (bytes that only exist in the longer file count as differing)
+  --max-runs N         maximum number of differing runs to list (default: unlimited)
+  (--render and --diff-report can't be combined.)
+
+Exit status: 0 on success, 1 if the files differ beyond the threshold, 2 on errors.";
+
+struct Args {
+    render: Option<PathBuf>,
+    diff_report: bool,
+    threshold: Option<u64>,
+    max_runs: Option<usize>,
+    level: Option<u32>,
+    block_pixels: u32,
+    color_mode: range_blocks::render::RenderColorMode,
+    files: Vec<PathBuf>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            render: None,
+            diff_report: false,
+            threshold: None,
+            max_runs: None,
+            level: None,
+            block_pixels: 1,
+            color_mode: range_blocks::render::RenderColorMode::Value,
+            files: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+            match arg.as_str() {
+                "--render" => parsed.render = Some(value()?.into()),
+                "--diff-report" => parsed.diff_report = true,
+                "--threshold" => {
+                    parsed.threshold = Some(value()?.parse().map_err(|e| format!("{e}"))?)
+                }
+                "--max-runs" => {
+                    parsed.max_runs = Some(value()?.parse().map_err(|e| format!("{e}"))?)
+                }
+                "--level" => parsed.level = Some(value()?.parse().map_err(|e| format!("{e}"))?),
+                "--block-pixels" => {
+                    parsed.block_pixels = value()?.parse().map_err(|e| format!("{e}"))?
+                }
+                "--color-mode" => parsed.color_mode = value()?.parse()?,
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+                _ => parsed.files.push(arg.into()),
+            }
+        }
+
+        if parsed.files.is_empty() || parsed.files.len() > 2 {
+            return Err("expected one or two files".to_string());
+        }
+        if parsed.diff_report && parsed.files.len() != 2 {
+            return Err("--diff-report needs two files".to_string());
+        }
+        if parsed.diff_report && parsed.render.is_some() {
+            return Err("--render and --diff-report can't be combined".to_string());
+        }
+        if !parsed.diff_report && (parsed.threshold.is_some() || parsed.max_runs.is_some()) {
+            return Err("--threshold and --max-runs need --diff-report".to_string());
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    use range_blocks::diff_report::DiffReport;
+    use range_blocks::max_recursion_level;
+    use range_blocks::render::{render, RenderOptions};
+
+    let files = args
+        .files
+        .iter()
+        .map(|path| std::fs::read(path).map_err(|e| format!("{}: {e}", path.display())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(out) = &args.render {
+        let image = render(
+            &files[0],
+            files.get(1).map(Vec::as_slice),
+            &RenderOptions {
+                recursion_level: args.level.unwrap_or(0),
+                block_pixels: args.block_pixels,
+                color_mode: args.color_mode,
+                sub_block_sqrt: range_blocks::DEFAULT_SUB_BLOCK_SQRT,
+            },
+        )?;
+        let file = std::fs::File::create(out)?;
+        image.write_png(std::io::BufWriter::new(file))?;
+        log::info!(
+            "wrote {}x{} image to {}",
+            image.width,
+            image.height,
+            out.display()
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.diff_report {
+        let sub_block_sqrt = range_blocks::DEFAULT_SUB_BLOCK_SQRT;
+        // (A per-byte report of large files would be far too long to use.)
+        let level = args.level.unwrap_or_else(|| {
+            let data_len = std::cmp::max(files[0].len(), files[1].len()) as u64;
+            max_recursion_level(data_len, sub_block_sqrt).saturating_sub(2)
+        });
+        let report = DiffReport::new(
+            &files[0],
+            &files[1],
+            level,
+            args.max_runs.unwrap_or(usize::MAX),
+            sub_block_sqrt,
+        );
+        serde_json::to_writer_pretty(std::io::stdout().lock(), &report)?;
+        println!();
+
+        return Ok(if report.exceeds(args.threshold.unwrap_or(0)) {
+            ExitCode::from(1)
+        } else {
+            ExitCode::SUCCESS
+        });
+    }
+
+    Err(format!("no mode selected\n\n{USAGE}").into())
+}
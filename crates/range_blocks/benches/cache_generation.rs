@@ -0,0 +1,59 @@
+//! Sequential vs. parallel `RangeBlockCache` generation on 1 GB inputs.
+//!
+//! Run with `cargo bench -p range_blocks --features parallel`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::Rng;
+use range_blocks::{
+    color::byte_color_rgb, RangeBlockCache, RangeBlockColorSum, RangeBlockDiff, RangeBlockSum,
+    DEFAULT_SUB_BLOCK_SQRT,
+};
+
+const DATA_LEN: usize = 1 << 30;
+
+fn random_data(len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    rand::thread_rng().fill(&mut data[..]);
+    data
+}
+
+fn cache_generation(c: &mut Criterion) {
+    let data0 = random_data(DATA_LEN);
+    let mut data1 = data0.clone();
+    // Some differences, so the diff cache has to descend into a few blocks.
+    for index in (0..DATA_LEN).step_by(DATA_LEN / 1000) {
+        data1[index] ^= 0xFF;
+    }
+
+    let mut group = c.benchmark_group("cache_generation_1GB");
+    group.sample_size(10);
+
+    let sum = RangeBlockSum::new(&data0);
+    group.bench_function("sum/sequential", |b| {
+        b.iter(|| RangeBlockCache::generate(&sum, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+    group.bench_function("sum/parallel", |b| {
+        b.iter(|| RangeBlockCache::generate_parallel(&sum, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+
+    let color_sum = RangeBlockColorSum::new(&data0, byte_color_rgb);
+    group.bench_function("color_sum/sequential", |b| {
+        b.iter(|| RangeBlockCache::generate(&color_sum, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+    group.bench_function("color_sum/parallel", |b| {
+        b.iter(|| RangeBlockCache::generate_parallel(&color_sum, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+
+    let diff = RangeBlockDiff::new(&data0, &data1);
+    group.bench_function("diff/sequential", |b| {
+        b.iter(|| RangeBlockCache::generate(&diff, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+    group.bench_function("diff/parallel", |b| {
+        b.iter(|| RangeBlockCache::generate_parallel(&diff, DATA_LEN, DEFAULT_SUB_BLOCK_SQRT))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, cache_generation);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//! `RangeBlockCache` memory use and lookup speed, compared with
+//! the `HashMap<(index, count), T>` storage it replaced.
+//!
+//! Run with `cargo bench -p range_blocks --bench cache_storage`.
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::Rng;
+use range_blocks::{
+    max_recursion_level, range_block_size, RangeBlockCache, RangeBlockIterator, RangeBlockSum,
+    DEFAULT_SUB_BLOCK_SQRT,
+};
+
+const DATA_LEN: usize = 1 << 30;
+
+/// The (index, count) of every cached range block.
+fn cached_blocks(data_len: u64) -> Vec<(u64, u64)> {
+    let max_recursion_level = max_recursion_level(data_len, DEFAULT_SUB_BLOCK_SQRT);
+    (2..=max_recursion_level)
+        .flat_map(|i| {
+            RangeBlockIterator::new(0, data_len, i, i, DEFAULT_SUB_BLOCK_SQRT, |_, _| true)
+        })
+        .collect()
+}
+
+fn cache_storage(c: &mut Criterion) {
+    let mut data = vec![0u8; DATA_LEN];
+    rand::thread_rng().fill(&mut data[..]);
+
+    let cache =
+        RangeBlockCache::generate(&RangeBlockSum::new(&data), DATA_LEN, DEFAULT_SUB_BLOCK_SQRT);
+    let blocks = cached_blocks(DATA_LEN as u64);
+    let hash_map: HashMap<(u64, u64), u64> = blocks
+        .iter()
+        .map(|&(index, count)| ((index, count), cache.get(index, count).unwrap()))
+        .collect();
+
+    // Approximate, for hashbrown: one control byte per bucket, buckets are a power of 2.
+    let hash_map_bytes =
+        hash_map.capacity().next_power_of_two() * (std::mem::size_of::<((u64, u64), u64)>() + 1);
+    println!(
+        "1 GB RangeBlockSum cache, {} values: dense {} bytes, HashMap ~{} bytes",
+        blocks.len(),
+        cache.memory_usage(),
+        hash_map_bytes,
+    );
+
+    // Lookups in a random order, like a zoomed-out view of the whole file.
+    let mut rng = rand::thread_rng();
+    let lookups: Vec<(u64, u64)> = (0..100_000)
+        .map(|_| blocks[rng.gen_range(0..blocks.len())])
+        .collect();
+    // Lookups of adjacent blocks at one level, like a zoomed-in view.
+    let count = range_block_size(3, DEFAULT_SUB_BLOCK_SQRT);
+    let adjacent_lookups: Vec<(u64, u64)> = (0..100_000).map(|k| (k * count, count)).collect();
+
+    let mut group = c.benchmark_group("cache_lookup_1GB");
+
+    for (name, lookups) in [("random", &lookups), ("adjacent", &adjacent_lookups)] {
+        group.bench_function(format!("{name}/dense"), |b| {
+            b.iter(|| {
+                lookups
+                    .iter()
+                    .map(|&(index, count)| cache.get(black_box(index), count).unwrap())
+                    .sum::<u64>()
+            })
+        });
+        group.bench_function(format!("{name}/hash_map"), |b| {
+            b.iter(|| {
+                lookups
+                    .iter()
+                    .map(|&(index, count)| hash_map[&(black_box(index), count)])
+                    .sum::<u64>()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, cache_storage);
+criterion_main!(benches);
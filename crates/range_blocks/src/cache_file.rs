@@ -0,0 +1,217 @@
+//! Saving and loading `RangeBlockCache`s, so large files don't need their caches regenerated
+//! every time they are opened.
+//!
+//! A cache file holds the `CacheKey` it was generated for, the cached values,
+//! and a checksum of both: files that are truncated, corrupted, or generated for
+//! different data or parameters are rejected, and should be regenerated.
+//!
+//! ```
+//! use range_blocks::cache_file::CacheKey;
+//! use range_blocks::{RangeBlockCache, RangeBlockSum};
+//!
+//! let data = vec![7u8; 10_000];
+//! let key = CacheKey::new(CacheKey::content_hash(&data), data.len(), 4, "sum");
+//! let cache = RangeBlockCache::generate(&RangeBlockSum::new(&data), data.len(), 4);
+//!
+//! let mut file = Vec::new();
+//! cache.write(&key, &mut file).unwrap();
+//!
+//! let loaded = RangeBlockCache::<u64>::read(&key, file.as_slice()).unwrap();
+//! assert_eq!(loaded.get(0, 4096), cache.get(0, 4096));
+//!
+//! // A single flipped bit is detected.
+//! let last = file.len() - 1;
+//! file[last] ^= 1;
+//! assert!(RangeBlockCache::<u64>::read(&key, file.as_slice()).is_err());
+//! ```
+
+use std::fmt;
+use std::io::{Read, Write};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::RangeBlockCache;
+
+/// Identifies the range block layout scheme, in case it ever changes.
+pub const LAYOUT: &str = "square";
+
+const MAGIC: &[u8; 8] = b"RBCACHE\0";
+const FORMAT_VERSION: u32 = 2;
+const CHECKSUM_LEN: usize = blake3::OUT_LEN;
+
+/// Everything a cache's values depend on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// See `CacheKey::content_hash`.
+    pub content_hash: [u8; 32],
+    pub data_len: u64,
+    pub sub_block_sqrt: u64,
+    /// See `LAYOUT`.
+    pub layout: String,
+    /// Identifies the `Cacheable` (and its color function, etc.) that generated the cache.
+    pub cacheable_id: String,
+}
+
+impl CacheKey {
+    pub fn new(
+        content_hash: [u8; 32],
+        data_len: usize,
+        sub_block_sqrt: u64,
+        cacheable_id: &str,
+    ) -> Self {
+        Self {
+            content_hash,
+            data_len: data_len as u64,
+            sub_block_sqrt,
+            layout: LAYOUT.to_string(),
+            cacheable_id: cacheable_id.to_string(),
+        }
+    }
+
+    /// Hash of the file contents that a cache was generated from.
+    pub fn content_hash(data: &[u8]) -> [u8; 32] {
+        blake3::hash(data).into()
+    }
+
+    /// A file name that is unique to this key.
+    pub fn file_name(&self) -> String {
+        let key_hash = blake3::hash(&bincode::serialize(self).expect("key should serialize"));
+        format!("{}.rbcache", key_hash.to_hex())
+    }
+}
+
+#[derive(Debug)]
+pub enum CacheFileError {
+    Io(std::io::Error),
+    /// Not a cache file, or a cache file from an incompatible version.
+    InvalidHeader,
+    ChecksumMismatch,
+    /// The cache file was generated for different data or parameters.
+    KeyMismatch,
+    /// The cached values don't match the layout of `CacheKey::data_len`.
+    InvalidValues,
+}
+
+impl fmt::Display for CacheFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::InvalidHeader => write!(f, "invalid cache file header"),
+            Self::ChecksumMismatch => write!(f, "cache file checksum mismatch"),
+            Self::KeyMismatch => write!(f, "cache file was generated for different data"),
+            Self::InvalidValues => write!(f, "invalid cached values"),
+        }
+    }
+}
+
+impl std::error::Error for CacheFileError {}
+
+impl From<std::io::Error> for CacheFileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> RangeBlockCache<T> {
+    /// Writes this cache, generated for `key`, in the cache file format.
+    pub fn write(&self, key: &CacheKey, mut writer: impl Write) -> Result<(), CacheFileError> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &(key, self.min_recursion_level, &self.levels))
+            .map_err(|_| CacheFileError::InvalidValues)?;
+        let checksum = blake3::hash(&bytes);
+        bytes.extend_from_slice(checksum.as_bytes());
+
+        writer.write_all(&bytes)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a cache file, which must have been written for `key`.
+    pub fn read(key: &CacheKey, mut reader: impl Read) -> Result<Self, CacheFileError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let header_len = MAGIC.len() + 4;
+        if bytes.len() < header_len + CHECKSUM_LEN
+            || &bytes[..MAGIC.len()] != MAGIC
+            || bytes[MAGIC.len()..header_len] != FORMAT_VERSION.to_le_bytes()
+        {
+            return Err(CacheFileError::InvalidHeader);
+        }
+
+        let (contents, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if blake3::hash(contents).as_bytes() != checksum {
+            return Err(CacheFileError::ChecksumMismatch);
+        }
+
+        let (file_key, min_recursion_level, levels): (CacheKey, u32, Vec<(u64, Vec<T>)>) =
+            bincode::deserialize(&contents[header_len..])
+                .map_err(|_| CacheFileError::InvalidValues)?;
+        if &file_key != key {
+            return Err(CacheFileError::KeyMismatch);
+        }
+
+        let cache = Self {
+            min_recursion_level,
+            levels,
+        };
+        if !cache.is_valid_for(key.data_len, key.sub_block_sqrt) {
+            return Err(CacheFileError::InvalidValues);
+        }
+
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RangeBlockDiff;
+
+    fn cache_file(key: &CacheKey, data0: &[u8], data1: &[u8]) -> Vec<u8> {
+        let diff = RangeBlockDiff::new(data0, data1);
+        let mut file = Vec::new();
+        RangeBlockCache::generate(&diff, data0.len(), key.sub_block_sqrt)
+            .write(key, &mut file)
+            .unwrap();
+        file
+    }
+
+    #[test]
+    fn rejected_cache_files() {
+        let data0 = vec![0u8; 5000];
+        let data1 = vec![1u8; 5000];
+        let key = CacheKey::new(CacheKey::content_hash(&data0), data0.len(), 4, "diff");
+        let file = cache_file(&key, &data0, &data1);
+        let read = |key: &CacheKey, file: &[u8]| RangeBlockCache::<Option<usize>>::read(key, file);
+
+        assert_eq!(read(&key, &file).unwrap().get(0, 4096), Some(Some(4096)));
+
+        // Truncated
+        assert!(matches!(
+            read(&key, &file[..file.len() - 1]),
+            Err(CacheFileError::ChecksumMismatch)
+        ));
+        assert!(matches!(
+            read(&key, &file[..10]),
+            Err(CacheFileError::InvalidHeader)
+        ));
+
+        // Different parameters
+        let other_key = CacheKey::new(key.content_hash, data0.len(), 4, "sum");
+        assert_ne!(key.file_name(), other_key.file_name());
+        assert!(matches!(
+            read(&other_key, &file),
+            Err(CacheFileError::KeyMismatch)
+        ));
+
+        // Values inconsistent with the key, but with a valid checksum
+        let short_key = CacheKey::new(key.content_hash, 100, 4, "diff");
+        let file = cache_file(&short_key, &data0[..1000], &data1[..1000]);
+        assert!(matches!(
+            read(&short_key, &file),
+            Err(CacheFileError::InvalidValues)
+        ));
+    }
+}
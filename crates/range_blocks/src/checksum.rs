@@ -0,0 +1,287 @@
+//! Checksums and cryptographic hashes of byte ranges, and searches for stored checksum values.
+//!
+//! ```
+//! use range_blocks::checksum::{adler32, find_stored_value, Crc, CrcParams};
+//!
+//! let crc = Crc::new(CrcParams::CRC_32);
+//! assert_eq!(crc.checksum(b"123456789"), 0xCBF4_3926);
+//! assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+//!
+//! // A CRC-32 stored after the data that it checks
+//! let mut data = b"123456789".to_vec();
+//! data.extend_from_slice(&0xCBF4_3926u32.to_le_bytes());
+//! let stored = find_stored_value(&data, 0xCBF4_3926, 4, 10);
+//! assert_eq!((stored[0].index, stored[0].big_endian), (9, false));
+//! ```
+
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// Parameters of a CRC algorithm, in the Rocksoft model (without separate input and output
+/// reflection: every common CRC reflects both or neither).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrcParams {
+    /// Width in bits: 8, 16, or 32.
+    pub width: u32,
+    /// The generator polynomial, without its top bit, in normal (MSB-first) form.
+    pub poly: u32,
+    pub init: u32,
+    /// `true` if bytes are processed least significant bit first.
+    pub reflected: bool,
+    pub xor_out: u32,
+}
+
+impl CrcParams {
+    pub const CRC_8: Self = Self::new(8, 0x07, 0, false, 0);
+    /// CRC-16/ARC
+    pub const CRC_16: Self = Self::new(16, 0x8005, 0, true, 0);
+    /// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE
+    pub const CRC_16_CCITT: Self = Self::new(16, 0x1021, 0xFFFF, false, 0);
+    pub const CRC_16_XMODEM: Self = Self::new(16, 0x1021, 0, false, 0);
+    /// CRC-32 (IEEE 802.3), as used by zip, PNG, and BPS.
+    pub const CRC_32: Self = Self::new(32, 0x04C1_1DB7, 0xFFFF_FFFF, true, 0xFFFF_FFFF);
+    /// CRC-32C (Castagnoli)
+    pub const CRC_32C: Self = Self::new(32, 0x1EDC_6F41, 0xFFFF_FFFF, true, 0xFFFF_FFFF);
+
+    /// Well-known CRC algorithms, and their names.
+    pub const PRESETS: [(&'static str, Self); 6] = [
+        ("CRC-8", Self::CRC_8),
+        ("CRC-16/ARC", Self::CRC_16),
+        ("CRC-16/CCITT-FALSE", Self::CRC_16_CCITT),
+        ("CRC-16/XMODEM", Self::CRC_16_XMODEM),
+        ("CRC-32", Self::CRC_32),
+        ("CRC-32C", Self::CRC_32C),
+    ];
+
+    pub const fn new(width: u32, poly: u32, init: u32, reflected: bool, xor_out: u32) -> Self {
+        Self {
+            width,
+            poly,
+            init,
+            reflected,
+            xor_out,
+        }
+    }
+
+    /// The name of the preset with these parameters, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        Self::PRESETS
+            .iter()
+            .find(|(_, params)| params == self)
+            .map(|&(name, _)| name)
+    }
+
+    /// The largest value that fits in `width` bits.
+    pub fn mask(&self) -> u32 {
+        u32::MAX >> (32 - self.width)
+    }
+}
+
+/// A table-driven CRC calculator.
+pub struct Crc {
+    params: CrcParams,
+    table: [u32; 256],
+}
+
+impl Crc {
+    /// # Panics
+    /// If `params.width` isn't 8, 16, or 32.
+    pub fn new(params: CrcParams) -> Self {
+        assert!(
+            matches!(params.width, 8 | 16 | 32),
+            "unsupported CRC width: {}",
+            params.width
+        );
+        let mask = params.mask();
+        let mut table = [0; 256];
+
+        for (byte, entry) in (0..=255u32).zip(&mut table) {
+            *entry = if params.reflected {
+                let poly = reflect(params.poly, params.width);
+                (0..8).fold(
+                    byte,
+                    |r, _| if r & 1 == 1 { (r >> 1) ^ poly } else { r >> 1 },
+                )
+            } else {
+                let top_bit = 1 << (params.width - 1);
+                (0..8).fold(byte << (params.width - 8), |r, _| {
+                    if r & top_bit != 0 {
+                        ((r << 1) ^ params.poly) & mask
+                    } else {
+                        (r << 1) & mask
+                    }
+                })
+            };
+        }
+
+        Self { params, table }
+    }
+
+    pub fn checksum(&self, data: &[u8]) -> u32 {
+        let CrcParams { width, init, .. } = self.params;
+
+        let crc = if self.params.reflected {
+            data.iter().fold(reflect(init, width), |r, &byte| {
+                (r >> 8) ^ self.table[((r ^ byte as u32) & 0xFF) as usize]
+            })
+        } else {
+            data.iter().fold(init, |r, &byte| {
+                let index = ((r >> (width - 8)) ^ byte as u32) & 0xFF;
+                ((r << 8) ^ self.table[index as usize]) & self.params.mask()
+            })
+        };
+
+        crc ^ self.params.xor_out
+    }
+}
+
+/// Reverses the order of the low `width` bits of `value`.
+fn reflect(value: u32, width: u32) -> u32 {
+    value.reverse_bits() >> (32 - width)
+}
+
+/// The Adler-32 checksum, as used by zlib.
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // The largest number of bytes that can be summed before `b` could overflow.
+    const CHUNK_LEN: usize = 5552;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(CHUNK_LEN) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Cryptographic hash algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [Self; 4] = [Self::Md5, Self::Sha1, Self::Sha256, Self::Blake3];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Blake3 => "BLAKE3",
+        }
+    }
+
+    /// The hash of `data`, as a lowercase hex string.
+    ///
+    /// ```
+    /// use range_blocks::checksum::HashAlgorithm;
+    ///
+    /// assert_eq!(HashAlgorithm::Md5.hash(b""), "d41d8cd98f00b204e9800998ecf8427e");
+    /// ```
+    pub fn hash(self, data: &[u8]) -> String {
+        let digest: Vec<u8> = match self {
+            Self::Md5 => Md5::digest(data).to_vec(),
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Blake3 => blake3::hash(data).as_bytes().to_vec(),
+        };
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// A location where a value is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoredValue {
+    pub index: u64,
+    pub big_endian: bool,
+}
+
+/// Finds the (first `limit`) locations where the low `len` bytes of `value` are stored in
+/// `data`, in either endianness. Single-byte values are reported as little-endian.
+pub fn find_stored_value(data: &[u8], value: u32, len: usize, limit: usize) -> Vec<StoredValue> {
+    let le_bytes = value.to_le_bytes();
+    let le_bytes = &le_bytes[..len.clamp(1, 4)];
+    let be_bytes: Vec<u8> = le_bytes.iter().rev().copied().collect();
+
+    data.windows(le_bytes.len())
+        .enumerate()
+        .filter_map(|(index, window)| {
+            let big_endian = if window == le_bytes {
+                false
+            } else if window == be_bytes {
+                true
+            } else {
+                return None;
+            };
+            Some(StoredValue {
+                index: index as u64,
+                big_endian,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_presets() {
+        // Check values from the CRC RevEng catalogue.
+        let check = |params| Crc::new(params).checksum(b"123456789");
+        assert_eq!(check(CrcParams::CRC_8), 0xF4);
+        assert_eq!(check(CrcParams::CRC_16), 0xBB3D);
+        assert_eq!(check(CrcParams::CRC_16_CCITT), 0x29B1);
+        assert_eq!(check(CrcParams::CRC_16_XMODEM), 0x31C3);
+        assert_eq!(check(CrcParams::CRC_32), 0xCBF4_3926);
+        assert_eq!(check(CrcParams::CRC_32C), 0xE306_9283);
+
+        // CRC-8/MAXIM-DOW: reflected, 8 bits wide
+        assert_eq!(check(CrcParams::new(8, 0x31, 0, true, 0)), 0xA1);
+        // CRC-32/BZIP2: not reflected
+        assert_eq!(
+            check(CrcParams::new(32, 0x04C1_1DB7, !0, false, !0)),
+            0xFC89_1918
+        );
+    }
+
+    #[test]
+    fn adler32_chunks() {
+        let data = vec![0xFF; 100_000];
+        let (a, b) = data.iter().fold((1u64, 0u64), |(a, b), &byte| {
+            let a = a + byte as u64;
+            (a, b + a)
+        });
+        assert_eq!(adler32(&data), (((b % 65521) << 16) | (a % 65521)) as u32);
+    }
+
+    #[test]
+    fn stored_values() {
+        let data = [0x12, 0x34, 0x56, 0x78, 0x56, 0x34, 0x12];
+        assert_eq!(
+            find_stored_value(&data, 0x123456, 3, 10),
+            vec![
+                StoredValue {
+                    index: 0,
+                    big_endian: true
+                },
+                StoredValue {
+                    index: 4,
+                    big_endian: false
+                }
+            ]
+        );
+        assert_eq!(find_stored_value(&data, 0x56, 1, 10).len(), 2);
+        assert_eq!(find_stored_value(&data, 0x56, 1, 1).len(), 1);
+    }
+}
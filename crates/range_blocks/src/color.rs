@@ -0,0 +1,640 @@
+//! Cell and range block coloring schemes.
+//!
+//! Colors are plain RGB values, so they can be used by any renderer.
+
+use serde::{Deserialize, Serialize};
+
+/// An 8-bit RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+    pub const GRAY: Self = Self::new(160, 160, 160);
+    pub const DARK_GREEN: Self = Self::new(0, 0x64, 0);
+    pub const LIGHT_GREEN: Self = Self::new(0x90, 0xEE, 0x90);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// The color channels as u64s, for summing in a `RangeBlockColorSum`.
+    pub fn to_u64s(self) -> (u64, u64, u64) {
+        (self.r as u64, self.g as u64, self.b as u64)
+    }
+}
+
+/// The cell coloring schemes that `BlockStats` keeps color sums for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Palette {
+    /// See `byte_color`.
+    Value,
+    /// See `semantic01_color`.
+    Semantic01,
+}
+
+impl Palette {
+    pub const ALL: [Self; 2] = [Self::Value, Self::Semantic01];
+
+    pub fn color(self, byte: u8) -> Rgb {
+        match self {
+            Self::Value => byte_color(byte),
+            Self::Semantic01 => semantic01_color(byte),
+        }
+    }
+}
+
+/// Color schemes for the color modes: the standard colors, or colorblind-safe colors
+/// whose ramps are based on the viridis or cividis colormaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorScheme {
+    Standard,
+    Viridis,
+    Cividis,
+}
+
+impl ColorScheme {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Viridis, Self::Cividis];
+
+    /// The colorblind-safe schemes' color for "nothing to compare" and "no differences":
+    /// a light gray that stands apart from their ramps (and from the gray background).
+    const NEUTRAL: Rgb = Rgb::new(224, 224, 224);
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Viridis => "Viridis",
+            Self::Cividis => "Cividis",
+        }
+    }
+
+    /// The scheme's ramp from 0.0 to 1.0 (`heat_color`, for `Standard`).
+    pub fn ramp(self, fraction: f32) -> Rgb {
+        match self {
+            Self::Standard => heat_color(fraction),
+            Self::Viridis => viridis(fraction),
+            Self::Cividis => cividis(fraction),
+        }
+    }
+
+    /// See `byte_color`. Colorblind-safe schemes ramp by value instead of splitting bits.
+    pub fn byte_color(self, byte: u8) -> Rgb {
+        match self {
+            Self::Standard => byte_color(byte),
+            _ => self.ramp(f32::from(byte) / 255.0),
+        }
+    }
+
+    /// See `semantic01_color`.
+    pub fn semantic01_color(self, value: u8) -> Rgb {
+        match self {
+            Self::Standard => semantic01_color(value),
+            _ if value == 0 => Rgb::BLACK,
+            _ if value == 255 => Rgb::WHITE,
+            _ if (value as char).is_ascii_graphic() => OKABE_ITO_SKY_BLUE,
+            _ => {
+                let v = 64 + value / 2;
+                Rgb::new(v, v, v)
+            }
+        }
+    }
+
+    /// See `diff_color`.
+    pub fn diff_color(self, diff_bytes: Option<usize>, count: u64) -> Rgb {
+        match (self, diff_bytes) {
+            (Self::Standard, _) => diff_color(diff_bytes, count),
+            (_, None) => Self::NEUTRAL,
+            (_, Some(0)) => self.ramp(0.0),
+            (_, Some(diff_bytes)) => self.ramp(0.4 + 0.6 * (diff_bytes as f32 / count as f32)),
+        }
+    }
+
+    /// See `variability_color`.
+    pub fn variability_color(self, differing_files: u32, file_count: usize) -> Rgb {
+        if self == Self::Standard {
+            return variability_color(differing_files, file_count);
+        }
+        let other_files = file_count.saturating_sub(1).max(1);
+        let fraction = (differing_files as f32 / other_files as f32).min(1.0);
+
+        if differing_files == 0 {
+            Self::NEUTRAL
+        } else {
+            self.ramp(0.25 + 0.75 * fraction)
+        }
+    }
+
+    /// See `printable_color`.
+    pub fn printable_color(self, fraction: f32) -> Rgb {
+        match self {
+            Self::Standard => printable_color(fraction),
+            _ => self.ramp(fraction),
+        }
+    }
+
+    /// See `entropy_color`.
+    pub fn entropy_color(self, entropy: f32) -> Rgb {
+        self.ramp(entropy)
+    }
+
+    /// See `range_color`.
+    pub fn range_color(self, range: u8) -> Rgb {
+        self.ramp(range as f32 / 255.0)
+    }
+
+    /// See `padding_color`. Colorblind-safe schemes use blue (0x00) and orange (0xFF)
+    /// from the Okabe-Ito palette.
+    pub fn padding_color(self, zero_fraction: f32, ff_fraction: f32) -> Rgb {
+        if self == Self::Standard {
+            return padding_color(zero_fraction, ff_fraction);
+        }
+        let zero = fraction_color(zero_fraction, OKABE_ITO_BLUE);
+        let ff = fraction_color(ff_fraction, OKABE_ITO_ORANGE);
+        Rgb::new(
+            zero.r.saturating_add(ff.r),
+            zero.g.saturating_add(ff.g),
+            zero.b.saturating_add(ff.b),
+        )
+    }
+
+    /// See `uniform_color`. Colorblind-safe schemes use colors from the Okabe-Ito palette.
+    pub fn uniform_color(self, uniform_value: Option<u8>) -> Rgb {
+        match (self, uniform_value) {
+            (Self::Standard, _) => uniform_color(uniform_value),
+            (_, Some(0x00)) => OKABE_ITO_BLUE,
+            (_, Some(0xFF)) => OKABE_ITO_ORANGE,
+            (_, Some(_)) => OKABE_ITO_REDDISH_PURPLE,
+            (_, None) => Rgb::new(48, 48, 48),
+        }
+    }
+}
+
+// Colorblind-safe categorical colors, from Okabe and Ito's palette.
+const OKABE_ITO_BLUE: Rgb = Rgb::new(0, 114, 178);
+const OKABE_ITO_ORANGE: Rgb = Rgb::new(230, 159, 0);
+const OKABE_ITO_SKY_BLUE: Rgb = Rgb::new(86, 180, 233);
+const OKABE_ITO_REDDISH_PURPLE: Rgb = Rgb::new(204, 121, 167);
+
+/// The viridis colormap, sampled at 9 evenly spaced points.
+const VIRIDIS: [Rgb; 9] = [
+    Rgb::new(0x44, 0x01, 0x54),
+    Rgb::new(0x47, 0x2D, 0x7B),
+    Rgb::new(0x3B, 0x52, 0x8B),
+    Rgb::new(0x2C, 0x72, 0x8E),
+    Rgb::new(0x21, 0x90, 0x8C),
+    Rgb::new(0x27, 0xAD, 0x81),
+    Rgb::new(0x5D, 0xC8, 0x63),
+    Rgb::new(0xAA, 0xDC, 0x32),
+    Rgb::new(0xFD, 0xE7, 0x25),
+];
+
+/// The cividis colormap, sampled at 9 evenly spaced points.
+const CIVIDIS: [Rgb; 9] = [
+    Rgb::new(0x00, 0x22, 0x4E),
+    Rgb::new(0x12, 0x35, 0x70),
+    Rgb::new(0x3B, 0x49, 0x6C),
+    Rgb::new(0x57, 0x5D, 0x6D),
+    Rgb::new(0x70, 0x71, 0x73),
+    Rgb::new(0x8A, 0x86, 0x78),
+    Rgb::new(0xA5, 0x9C, 0x74),
+    Rgb::new(0xC3, 0xB3, 0x69),
+    Rgb::new(0xFE, 0xE8, 0x38),
+];
+
+/// Linearly interpolates between a colormap's evenly spaced samples.
+fn colormap(samples: &[Rgb], fraction: f32) -> Rgb {
+    let position = fraction.clamp(0.0, 1.0) * (samples.len() - 1) as f32;
+    let i = (position as usize).min(samples.len() - 2);
+    let t = position - i as f32;
+    let (a, b) = (samples[i], samples[i + 1]);
+    let lerp = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Rgb::new(lerp(a.r, b.r), lerp(a.g, b.g), lerp(a.b, b.b))
+}
+
+/// The viridis colormap (perceptually uniform and colorblind-safe): dark purple (0.0)
+/// through teal to yellow (1.0).
+pub fn viridis(fraction: f32) -> Rgb {
+    colormap(&VIRIDIS, fraction)
+}
+
+/// The cividis colormap (optimized for color vision deficiency): dark blue (0.0)
+/// through gray to yellow (1.0).
+pub fn cividis(fraction: f32) -> Rgb {
+    colormap(&CIVIDIS, fraction)
+}
+
+/// The relative luminance of `color`, from 0.0 (black) to 1.0 (white), as defined by WCAG.
+pub fn relative_luminance(color: Rgb) -> f32 {
+    let linear = |channel: u8| {
+        let c = f32::from(channel) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// Black or white, whichever has more contrast with `background`.
+///
+/// ```
+/// use range_blocks::color::{text_color, Rgb};
+///
+/// assert_eq!(text_color(Rgb::new(255, 255, 0)), Rgb::BLACK);
+/// assert_eq!(text_color(Rgb::new(0, 0, 128)), Rgb::WHITE);
+/// ```
+pub fn text_color(background: Rgb) -> Rgb {
+    let luminance = relative_luminance(background);
+    // WCAG contrast ratios with white and black text.
+    if (1.05 / (luminance + 0.05)) >= ((luminance + 0.05) / 0.05) {
+        Rgb::WHITE
+    } else {
+        Rgb::BLACK
+    }
+}
+
+/// A class of byte values, which a `ByteRule` maps to a color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ByteClass {
+    /// Values from `start` through `end` (inclusive).
+    Range {
+        start: u8,
+        end: u8,
+    },
+    Zero,
+    Ff,
+    /// Printable ASCII, including space.
+    Printable,
+    /// ASCII whitespace.
+    Whitespace,
+    /// ASCII control characters (other than whitespace).
+    Control,
+    /// 0x80 and above.
+    HighBit,
+}
+
+impl ByteClass {
+    /// The classes defined by a predicate, rather than a range.
+    pub const PREDICATES: [Self; 6] = [
+        Self::Zero,
+        Self::Ff,
+        Self::Printable,
+        Self::Whitespace,
+        Self::Control,
+        Self::HighBit,
+    ];
+
+    pub fn contains(self, byte: u8) -> bool {
+        match self {
+            Self::Range { start, end } => (start..=end).contains(&byte),
+            Self::Zero => byte == 0x00,
+            Self::Ff => byte == 0xFF,
+            Self::Printable => byte == b' ' || byte.is_ascii_graphic(),
+            Self::Whitespace => byte.is_ascii_whitespace(),
+            Self::Control => byte.is_ascii_control() && !byte.is_ascii_whitespace(),
+            Self::HighBit => byte >= 0x80,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Range { .. } => "Range",
+            Self::Zero => "0x00",
+            Self::Ff => "0xFF",
+            Self::Printable => "Printable ASCII",
+            Self::Whitespace => "ASCII whitespace",
+            Self::Control => "ASCII control",
+            Self::HighBit => "High bit set",
+        }
+    }
+}
+
+/// Colors the bytes in `class`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteRule {
+    pub class: ByteClass,
+    pub color: Rgb,
+}
+
+/// A color at a byte value in a `PaletteScheme::Gradient`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GradientStop {
+    pub byte: u8,
+    pub color: Rgb,
+}
+
+/// How a `UserPalette` maps byte values to colors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteScheme {
+    /// The color of the first rule whose class contains the byte, or `fallback` if none do.
+    Rules { rules: Vec<ByteRule>, fallback: Rgb },
+    /// Interpolated between the nearest stops on either side of the byte
+    /// (the nearest stop's color past the first or last stop, black if there are none).
+    Gradient { stops: Vec<GradientStop> },
+}
+
+/// A user-defined cell coloring scheme.
+///
+/// ```
+/// use range_blocks::color::{ByteClass, ByteRule, PaletteScheme, Rgb, UserPalette};
+///
+/// let palette = UserPalette {
+///     name: "Zeros".to_string(),
+///     scheme: PaletteScheme::Rules {
+///         rules: vec![ByteRule { class: ByteClass::Zero, color: Rgb::BLACK }],
+///         fallback: Rgb::GRAY,
+///     },
+/// };
+/// assert_eq!(palette.color(0x00), Rgb::BLACK);
+/// assert_eq!(palette.color(0x41), Rgb::GRAY);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserPalette {
+    pub name: String,
+    pub scheme: PaletteScheme,
+}
+
+impl UserPalette {
+    pub fn color(&self, byte: u8) -> Rgb {
+        match &self.scheme {
+            PaletteScheme::Rules { rules, fallback } => rules
+                .iter()
+                .find(|rule| rule.class.contains(byte))
+                .map_or(*fallback, |rule| rule.color),
+            PaletteScheme::Gradient { stops } => {
+                let below = stops
+                    .iter()
+                    .filter(|stop| stop.byte <= byte)
+                    .max_by_key(|stop| stop.byte);
+                let above = stops
+                    .iter()
+                    .filter(|stop| stop.byte >= byte)
+                    .min_by_key(|stop| stop.byte);
+
+                match (below, above) {
+                    (Some(below), Some(above)) if below.byte != above.byte => {
+                        let t = f32::from(byte - below.byte) / f32::from(above.byte - below.byte);
+                        let lerp = |a: u8, b: u8| {
+                            (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8
+                        };
+                        Rgb::new(
+                            lerp(below.color.r, above.color.r),
+                            lerp(below.color.g, above.color.g),
+                            lerp(below.color.b, above.color.b),
+                        )
+                    }
+                    (Some(stop), _) | (None, Some(stop)) => stop.color,
+                    (None, None) => Rgb::BLACK,
+                }
+            }
+        }
+    }
+
+    /// The color of every byte value, for fast lookup (e.g. in a `RangeBlockColorSum`).
+    pub fn color_table(&self) -> [Rgb; 256] {
+        std::array::from_fn(|byte| self.color(byte as u8))
+    }
+}
+
+/// Generate a representative color from `byte` by distributing its bits across
+/// the most significant bits of the color's RGB color values.
+///
+/// ```
+/// use range_blocks::color::{byte_color, Rgb};
+///
+/// assert_eq!(byte_color(0b11_010_001), Rgb::new(0b11000000, 0b01000000, 0b00100000));
+/// ```
+pub fn byte_color(byte: u8) -> Rgb {
+    let r = byte & 0b11000000;
+    let g = (byte & 0b00111000) << 2;
+    let b = (byte & 0b00000111) << 5;
+
+    Rgb::new(r, g, b)
+}
+
+/// Same as `byte_color`, but outputs the RGB values as 3 u64s.
+pub fn byte_color_rgb(byte: u8) -> (u64, u64, u64) {
+    byte_color(byte).to_u64s()
+}
+
+/// Generates a representative diff color.
+pub fn diff_color(diff_bytes: Option<usize>, count: u64) -> Rgb {
+    if let Some(diff_bytes) = diff_bytes {
+        if diff_bytes == 0 {
+            Rgb::new(127, 127, 127)
+        } else {
+            let diff = 255.0 * (1.0 - (diff_bytes as f32 / count as f32));
+            Rgb::new(255, diff as u8, diff as u8)
+        }
+    } else {
+        Rgb::BLACK
+    }
+}
+
+/// Generates a representative variability color: black if no files differ from the reference,
+/// ramping up through blue to white as more of the other `file_count - 1` files differ.
+pub fn variability_color(differing_files: u32, file_count: usize) -> Rgb {
+    let other_files = file_count.saturating_sub(1).max(1);
+    let fraction = (differing_files as f32 / other_files as f32).min(1.0);
+
+    if differing_files == 0 {
+        Rgb::BLACK
+    } else {
+        let v = (255.0 * fraction) as u8;
+        Rgb::new(v, v, 255)
+    }
+}
+
+/// (Experimental) Generates a representative color from `value`.
+pub fn semantic01_color(value: u8) -> Rgb {
+    if value == 0 {
+        Rgb::DARK_GREEN
+    } else if value == 255 {
+        Rgb::LIGHT_GREEN
+    } else if (value as char).is_ascii_graphic() {
+        Rgb::new(value, value, 255)
+    } else {
+        let v = 64 + value / 2;
+        Rgb::new(v, v, v)
+    }
+}
+
+/// Same as `semantic01_color`, but outputs the RGB values as 3 u64s.
+pub fn semantic01_color_rgb(value: u8) -> (u64, u64, u64) {
+    semantic01_color(value).to_u64s()
+}
+
+/// Ramps from black (0.0) through `color` (1.0).
+pub fn fraction_color(fraction: f32, color: Rgb) -> Rgb {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let scale = |channel: u8| (channel as f32 * fraction) as u8;
+    Rgb::new(scale(color.r), scale(color.g), scale(color.b))
+}
+
+/// Heat map ramp: black (0.0) through red and yellow to white (1.0).
+pub fn heat_color(fraction: f32) -> Rgb {
+    let v = (fraction.clamp(0.0, 1.0) * 3.0 * 255.0) as u32;
+    let channel = |offset: u32| v.saturating_sub(offset).min(255) as u8;
+    Rgb::new(channel(0), channel(255), channel(510))
+}
+
+/// Generates a representative color from the fraction of printable ASCII bytes.
+pub fn printable_color(fraction: f32) -> Rgb {
+    fraction_color(fraction, Rgb::new(128, 128, 255))
+}
+
+/// Generates a representative color from an entropy value, from 0.0 (uniform) to 1.0 (random).
+pub fn entropy_color(entropy: f32) -> Rgb {
+    heat_color(entropy)
+}
+
+/// Generates a representative color from the range (max - min) of a block's byte values.
+pub fn range_color(range: u8) -> Rgb {
+    heat_color(range as f32 / 255.0)
+}
+
+/// Generates a representative color from the fractions of 0x00 (blue) and 0xFF (orange) bytes.
+pub fn padding_color(zero_fraction: f32, ff_fraction: f32) -> Rgb {
+    let zero = fraction_color(zero_fraction, Rgb::new(0, 0, 255));
+    let ff = fraction_color(ff_fraction, Rgb::new(255, 160, 0));
+    Rgb::new(
+        zero.r.saturating_add(ff.r),
+        zero.g.saturating_add(ff.g),
+        zero.b.saturating_add(ff.b),
+    )
+}
+
+/// Highlights blocks whose bytes all have the same value: 0x00 (blue), 0xFF (orange),
+/// or any other value (magenta). Other blocks are dark gray.
+pub fn uniform_color(uniform_value: Option<u8>) -> Rgb {
+    match uniform_value {
+        Some(0x00) => Rgb::new(0, 0, 255),
+        Some(0xFF) => Rgb::new(255, 160, 0),
+        Some(_) => Rgb::new(255, 0, 255),
+        None => Rgb::new(48, 48, 48),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colorblind_safe_ramps() {
+        for scheme in [ColorScheme::Viridis, ColorScheme::Cividis] {
+            assert_eq!(scheme.ramp(0.0), scheme.ramp(-1.0));
+            assert_eq!(scheme.ramp(1.0), scheme.ramp(2.0));
+
+            // Brightness increases monotonically, so the ramps read without hue.
+            let luminances: Vec<f32> = (0..=64)
+                .map(|i| relative_luminance(scheme.ramp(i as f32 / 64.0)))
+                .collect();
+            assert!(luminances.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+        assert_eq!(viridis(0.0), VIRIDIS[0]);
+        assert_eq!(cividis(1.0), CIVIDIS[8]);
+    }
+
+    #[test]
+    fn colorblind_safe_special_colors_are_distinct() {
+        // The largest difference of any channel.
+        let distance = |a: Rgb, b: Rgb| {
+            [(a.r, b.r), (a.g, b.g), (a.b, b.b)]
+                .into_iter()
+                .map(|(a, b)| a.abs_diff(b))
+                .max()
+                .unwrap()
+        };
+        let assert_distinct = |colors: &[Rgb]| {
+            for (i, &a) in colors.iter().enumerate() {
+                for &b in &colors[i + 1..] {
+                    assert!(distance(a, b) >= 48, "{a:?} and {b:?} are too similar");
+                }
+            }
+        };
+
+        for scheme in [ColorScheme::Viridis, ColorScheme::Cividis] {
+            // Past the end, identical, a few bytes differ, and the gray background.
+            assert_distinct(&[
+                scheme.diff_color(None, 256),
+                scheme.diff_color(Some(0), 256),
+                scheme.diff_color(Some(1), 256),
+                Rgb::GRAY,
+            ]);
+            // No files differ, one of many differs, and the gray background.
+            assert_distinct(&[
+                scheme.variability_color(0, 64),
+                scheme.variability_color(1, 64),
+                Rgb::GRAY,
+            ]);
+        }
+    }
+
+    #[test]
+    fn palette_rules() {
+        let red = Rgb::new(255, 0, 0);
+        let palette = UserPalette {
+            name: String::new(),
+            scheme: PaletteScheme::Rules {
+                rules: vec![
+                    ByteRule {
+                        class: ByteClass::Range {
+                            start: 0x30,
+                            end: 0x39,
+                        },
+                        color: red,
+                    },
+                    ByteRule {
+                        class: ByteClass::Printable,
+                        color: Rgb::LIGHT_GREEN,
+                    },
+                ],
+                fallback: Rgb::GRAY,
+            },
+        };
+
+        // The first matching rule wins.
+        assert_eq!(palette.color(b'5'), red);
+        assert_eq!(palette.color(b'A'), Rgb::LIGHT_GREEN);
+        assert_eq!(palette.color(0x80), Rgb::GRAY);
+    }
+
+    #[test]
+    fn palette_gradient() {
+        let white = Rgb::new(255, 255, 255);
+        let mut palette = UserPalette {
+            name: String::new(),
+            scheme: PaletteScheme::Gradient {
+                // (Unsorted)
+                stops: vec![
+                    GradientStop {
+                        byte: 0xC0,
+                        color: white,
+                    },
+                    GradientStop {
+                        byte: 0x40,
+                        color: Rgb::BLACK,
+                    },
+                ],
+            },
+        };
+
+        assert_eq!(palette.color(0x00), Rgb::BLACK);
+        assert_eq!(palette.color(0x40), Rgb::BLACK);
+        assert_eq!(palette.color(0x80), Rgb::new(128, 128, 128));
+        assert_eq!(palette.color(0xC0), white);
+        assert_eq!(palette.color(0xFF), white);
+
+        let table = palette.color_table();
+        assert!((0..=255).all(|byte| table[byte as usize] == palette.color(byte)));
+
+        palette.scheme = PaletteScheme::Gradient { stops: Vec::new() };
+        assert_eq!(palette.color(0x80), Rgb::BLACK);
+    }
+}
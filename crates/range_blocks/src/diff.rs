@@ -0,0 +1,340 @@
+//! Navigation over the differences between two files.
+//!
+//! Searches are guided by a diff `RangeBlockCache` (see `RangeBlockDiff`):
+//! range blocks with no differing bytes are skipped whole, so only blocks that contain
+//! differences are ever descended into.
+//!
+//! ```
+//! use range_blocks::diff::{DiffRun, DiffSearch};
+//! use range_blocks::{RangeBlockCache, RangeBlockDiff, DEFAULT_SUB_BLOCK_SQRT};
+//!
+//! let data0 = vec![0u8; 1000];
+//! let mut data1 = data0.clone();
+//! data1[300..302].fill(1);
+//!
+//! let diff = RangeBlockDiff::new(&data0, &data1);
+//! let cache = RangeBlockCache::generate(&diff, data0.len(), DEFAULT_SUB_BLOCK_SQRT);
+//! let search = DiffSearch::new(&data0, &data1, &cache, DEFAULT_SUB_BLOCK_SQRT);
+//!
+//! assert_eq!(search.next_difference(0), Some(300));
+//! assert_eq!(search.runs(10), vec![DiffRun { index: 300, len: 2 }]);
+//! ```
+
+use serde::Serialize;
+
+use crate::{
+    max_recursion_level, range_block_size, Cacheable, RangeBlockCache, RangeBlockDiff,
+    RangeBlockIterator,
+};
+
+/// A contiguous run of differing bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffRun {
+    pub index: u64,
+    pub len: u64,
+}
+
+/// Searches the differences between two files.
+pub struct DiffSearch<'a> {
+    diff: RangeBlockDiff<'a>,
+    diff_cache: &'a RangeBlockCache<Option<usize>>,
+    /// The length of the range that both files cover: differing bytes are found within it.
+    data_len: u64,
+    /// The length of the longer file: bytes from `data_len` to here differ, if it's longer.
+    max_len: u64,
+    max_recursion_level: u32,
+    sub_block_sqrt: u64,
+}
+
+impl<'a> DiffSearch<'a> {
+    /// `diff_cache` should be generated from `RangeBlockDiff::new(data0, data1)`, or be empty.
+    pub fn new(
+        data0: &'a [u8],
+        data1: &'a [u8],
+        diff_cache: &'a RangeBlockCache<Option<usize>>,
+        sub_block_sqrt: u64,
+    ) -> Self {
+        let data_len = std::cmp::min(data0.len(), data1.len()) as u64;
+        Self {
+            diff: RangeBlockDiff::new(data0, data1),
+            diff_cache,
+            data_len,
+            max_len: std::cmp::max(data0.len(), data1.len()) as u64,
+            max_recursion_level: max_recursion_level(data_len, sub_block_sqrt),
+            sub_block_sqrt,
+        }
+    }
+
+    /// The number of differing bytes in the range block (within `data_len`).
+    fn diff_bytes(&self, index: u64, count: u64) -> u64 {
+        let diff_bytes = self
+            .diff_cache
+            .get(index, count)
+            .unwrap_or_else(|| self.diff.value(index, count));
+
+        diff_bytes.unwrap_or(0) as u64
+    }
+
+    /// `true` IFF the range block contains at least one differing byte.
+    fn has_difference(&self, index: u64, count: u64) -> bool {
+        self.diff_bytes(index, count) > 0
+    }
+
+    /// `Iterator` over the indices of differing bytes, starting at `start_index`.
+    fn differences_from(&self, start_index: u64) -> impl Iterator<Item = u64> + '_ {
+        RangeBlockIterator::new(
+            start_index,
+            self.data_len,
+            0,
+            self.max_recursion_level,
+            self.sub_block_sqrt,
+            |index, count| self.has_difference(index, count),
+        )
+        .map(|(index, _)| index)
+    }
+
+    /// The first differing byte index at or after `start_index`.
+    pub fn next_difference(&self, start_index: u64) -> Option<u64> {
+        self.differences_from(start_index).next()
+    }
+
+    /// The last differing byte index before `limit_index`.
+    pub fn previous_difference(&self, limit_index: u64) -> Option<u64> {
+        if self.data_len == 0 {
+            return None;
+        }
+
+        let count = range_block_size(self.max_recursion_level, self.sub_block_sqrt);
+        self.last_difference_within(0, count, self.max_recursion_level, limit_index)
+    }
+
+    /// Recursive search for the last differing byte index before `limit_index`
+    /// in a range block at `recursion_level`.
+    fn last_difference_within(
+        &self,
+        index: u64,
+        count: u64,
+        recursion_level: u32,
+        limit_index: u64,
+    ) -> Option<u64> {
+        if index >= limit_index || !self.has_difference(index, count) {
+            return None;
+        }
+        if recursion_level == 0 {
+            return Some(index);
+        }
+
+        let sub_block_count = self.sub_block_sqrt * self.sub_block_sqrt;
+        let sub_count = count / sub_block_count;
+
+        (0..sub_block_count).rev().find_map(|i| {
+            self.last_difference_within(
+                index + i * sub_count,
+                sub_count,
+                recursion_level - 1,
+                limit_index,
+            )
+        })
+    }
+
+    /// Contiguous runs of differing bytes, in index order, including the bytes past the end
+    /// of the shorter file. Stops after `max_runs` runs have been found.
+    pub fn runs(&self, max_runs: usize) -> Vec<DiffRun> {
+        let mut runs: Vec<DiffRun> = Vec::new();
+
+        let count = range_block_size(self.max_recursion_level, self.sub_block_sqrt);
+        let complete = self.data_len == 0 || self.runs_within(0, count, max_runs, &mut runs);
+        if complete && self.max_len > self.data_len {
+            let tail = DiffRun {
+                index: self.data_len,
+                len: self.max_len - self.data_len,
+            };
+            add_run(&mut runs, tail, max_runs);
+        }
+
+        runs
+    }
+
+    /// Adds the runs of differing bytes in a range block to `runs`: range blocks whose bytes
+    /// all differ are added whole, without descending into them.
+    /// Returns `false` if `max_runs` was reached.
+    fn runs_within(
+        &self,
+        index: u64,
+        count: u64,
+        max_runs: usize,
+        runs: &mut Vec<DiffRun>,
+    ) -> bool {
+        if index >= self.data_len {
+            return true;
+        }
+        let diff_bytes = self.diff_bytes(index, count);
+        if diff_bytes == 0 {
+            return true;
+        }
+
+        // (The final range block may be incomplete.)
+        let len = count.min(self.data_len - index);
+        if diff_bytes == len {
+            return add_run(runs, DiffRun { index, len }, max_runs);
+        }
+
+        let sub_block_count = self.sub_block_sqrt * self.sub_block_sqrt;
+        let sub_count = count / sub_block_count;
+        (0..sub_block_count)
+            .all(|i| self.runs_within(index + i * sub_count, sub_count, max_runs, runs))
+    }
+}
+
+/// Adds `run` to `runs`, or extends the last run if they are contiguous.
+/// Returns `false` (without adding it) if `run` would be run number `max_runs + 1`.
+fn add_run(runs: &mut Vec<DiffRun>, run: DiffRun, max_runs: usize) -> bool {
+    let run_count = runs.len();
+    match runs.last_mut() {
+        Some(last) if last.index + last.len == run.index => last.len += run.len,
+        _ if run_count == max_runs => return false,
+        _ => runs.push(run),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// Random file pair with sparse, clustered differences.
+    fn random_pair(len: usize) -> (Vec<u8>, Vec<u8>) {
+        let mut rng = rand::thread_rng();
+        let data0: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let mut data1 = data0.clone();
+        for _ in 0..rng.gen_range(0..8) {
+            let start = rng.gen_range(0..len);
+            let end = (start + rng.gen_range(1..64)).min(len);
+            data1[start..end]
+                .iter_mut()
+                .for_each(|x| *x = x.wrapping_add(1));
+        }
+        data1.truncate(len - rng.gen_range(0..len / 4));
+        (data0, data1)
+    }
+
+    fn brute_force_differences(data0: &[u8], data1: &[u8]) -> Vec<u64> {
+        data0
+            .iter()
+            .zip(data1)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| i as u64)
+            .collect()
+    }
+
+    #[test]
+    fn test_next_and_previous_difference() {
+        for _ in 0..20 {
+            let (data0, data1) = random_pair(5000);
+            let diff_cache =
+                RangeBlockCache::generate(&RangeBlockDiff::new(&data0, &data1), data0.len(), 4);
+            let search = DiffSearch::new(&data0, &data1, &diff_cache, 4);
+            let expected = brute_force_differences(&data0, &data1);
+
+            for start in (0..data0.len() as u64).step_by(97) {
+                assert_eq!(
+                    search.next_difference(start),
+                    expected.iter().copied().find(|&i| i >= start)
+                );
+                assert_eq!(
+                    search.previous_difference(start),
+                    expected.iter().copied().rev().find(|&i| i < start)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_runs() {
+        let data0 = [0u8; 100];
+        let mut data1 = [0u8; 100];
+        data1[3..7].fill(1);
+        data1[7] = 2;
+        data1[20] = 1;
+        data1[99] = 1;
+
+        let diff_cache = RangeBlockCache::new();
+        let search = DiffSearch::new(&data0, &data1, &diff_cache, 4);
+
+        assert_eq!(
+            search.runs(usize::MAX),
+            vec![
+                DiffRun { index: 3, len: 5 },
+                DiffRun { index: 20, len: 1 },
+                DiffRun { index: 99, len: 1 },
+            ]
+        );
+        assert_eq!(search.runs(1), vec![DiffRun { index: 3, len: 5 }]);
+    }
+
+    #[test]
+    fn test_runs_match_brute_force() {
+        for _ in 0..20 {
+            let (data0, data1) = random_pair(5000);
+            let diff_cache =
+                RangeBlockCache::generate(&RangeBlockDiff::new(&data0, &data1), data0.len(), 4);
+            let search = DiffSearch::new(&data0, &data1, &diff_cache, 4);
+
+            // The bytes past the end of the shorter file differ too.
+            let mut expected: Vec<DiffRun> = Vec::new();
+            let differences = brute_force_differences(&data0, &data1)
+                .into_iter()
+                .chain(data1.len() as u64..data0.len() as u64);
+            for index in differences {
+                match expected.last_mut() {
+                    Some(run) if run.index + run.len == index => run.len += 1,
+                    _ => expected.push(DiffRun { index, len: 1 }),
+                }
+            }
+
+            assert_eq!(search.runs(usize::MAX), expected);
+            assert_eq!(search.runs(2), expected[..expected.len().min(2)]);
+        }
+    }
+
+    #[test]
+    fn test_runs_of_whole_blocks() {
+        // Entirely different files, of different lengths.
+        let (data0, data1) = (vec![0u8; 5000], vec![1u8; 4000]);
+        let diff_cache =
+            RangeBlockCache::generate(&RangeBlockDiff::new(&data0, &data1), data1.len(), 4);
+        let search = DiffSearch::new(&data0, &data1, &diff_cache, 4);
+
+        assert_eq!(
+            search.runs(1),
+            vec![DiffRun {
+                index: 0,
+                len: 5000
+            }]
+        );
+
+        // A run ending at the end of the shorter file joins the size difference.
+        let mut data1 = data0[..4000].to_vec();
+        data1[1000..1200].fill(1);
+        data1[3500..].fill(1);
+        let diff_cache =
+            RangeBlockCache::generate(&RangeBlockDiff::new(&data0, &data1), data1.len(), 4);
+        let search = DiffSearch::new(&data0, &data1, &diff_cache, 4);
+
+        assert_eq!(
+            search.runs(10),
+            vec![
+                DiffRun {
+                    index: 1000,
+                    len: 200
+                },
+                DiffRun {
+                    index: 3500,
+                    len: 1500
+                },
+            ]
+        );
+    }
+}
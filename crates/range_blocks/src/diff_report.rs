@@ -0,0 +1,176 @@
+//! Machine-readable statistics about the differences between two files.
+
+use serde::Serialize;
+
+use crate::diff::{DiffRun, DiffSearch};
+use crate::{max_recursion_level, Cacheable, RangeBlockCache, RangeBlockDiff, RangeBlockIterator};
+
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    pub len0: u64,
+    pub len1: u64,
+    /// `len1 - len0`
+    pub size_difference: i64,
+    /// Differing bytes within the range that both files cover.
+    pub differing_bytes: u64,
+    pub recursion_level: u32,
+    /// Diff counts of every range block at `recursion_level`.
+    pub blocks: Vec<BlockDiff>,
+    /// Contiguous runs of differing bytes (including the bytes past the end of the shorter file).
+    pub runs: Vec<DiffRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockDiff {
+    pub index: u64,
+    pub count: u64,
+    /// `None` if the block is entirely past the end of either file.
+    pub differing_bytes: Option<usize>,
+}
+
+impl DiffReport {
+    /// Compares `data0` and `data1`, listing per-block diff counts at `recursion_level`
+    /// (clamped to the maximum level) and at most `max_runs` differing runs.
+    pub fn new(
+        data0: &[u8],
+        data1: &[u8],
+        recursion_level: u32,
+        max_runs: usize,
+        sub_block_sqrt: u64,
+    ) -> Self {
+        let data_len = std::cmp::max(data0.len(), data1.len()) as u64;
+        let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+        let recursion_level = recursion_level.min(max_recursion_level);
+
+        let diff = RangeBlockDiff::new(data0, data1);
+        let diff_cache = RangeBlockCache::generate(&diff, data_len as usize, sub_block_sqrt);
+
+        let blocks: Vec<BlockDiff> = RangeBlockIterator::new(
+            0,
+            data_len,
+            recursion_level,
+            max_recursion_level,
+            sub_block_sqrt,
+            |_, _| true,
+        )
+        .map(|(index, count)| BlockDiff {
+            index,
+            count,
+            differing_bytes: diff_cache
+                .get(index, count)
+                .unwrap_or_else(|| diff.value(index, count)),
+        })
+        .collect();
+
+        let differing_bytes = blocks
+            .iter()
+            .filter_map(|block| block.differing_bytes)
+            .sum::<usize>() as u64;
+
+        let runs = DiffSearch::new(data0, data1, &diff_cache, sub_block_sqrt).runs(max_runs);
+
+        Self {
+            len0: data0.len() as u64,
+            len1: data1.len() as u64,
+            size_difference: data1.len() as i64 - data0.len() as i64,
+            differing_bytes,
+            recursion_level,
+            blocks,
+            runs,
+        }
+    }
+
+    /// Differing bytes, counting each byte that only exists in the longer file as different.
+    pub fn total_difference(&self) -> u64 {
+        self.differing_bytes + self.size_difference.unsigned_abs()
+    }
+
+    /// `true` IFF the files differ by more than `threshold` bytes (see `total_difference`).
+    pub fn exceeds(&self, threshold: u64) -> bool {
+        self.total_difference() > threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let data0 = vec![0u8; 1000];
+        let mut data1 = vec![0u8; 1100];
+        data1[10..20].fill(1);
+        data1[600] = 1;
+
+        let report = DiffReport::new(&data0, &data1, 2, usize::MAX, 4);
+
+        assert_eq!((report.len0, report.len1), (1000, 1100));
+        assert_eq!(report.size_difference, 100);
+        assert_eq!(report.differing_bytes, 11);
+        assert_eq!(report.total_difference(), 111);
+
+        // Level 2 range blocks are 256 bytes: the last one is past the end of `data0`.
+        assert_eq!(report.recursion_level, 2);
+        let blocks: Vec<_> = report
+            .blocks
+            .iter()
+            .map(|block| (block.index, block.count, block.differing_bytes))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (0, 256, Some(10)),
+                (256, 256, Some(0)),
+                (512, 256, Some(1)),
+                (768, 256, Some(0)),
+                (1024, 256, None),
+            ]
+        );
+
+        assert_eq!(
+            report.runs,
+            vec![
+                DiffRun { index: 10, len: 10 },
+                DiffRun { index: 600, len: 1 },
+                DiffRun {
+                    index: 1000,
+                    len: 100
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_runs_and_recursion_level() {
+        let data0 = vec![0u8; 1000];
+        let data1: Vec<u8> = (0..1000).map(|i| (i % 2) as u8).collect();
+
+        let report = DiffReport::new(&data0, &data1, 99, 3, 4);
+
+        // Clamped to the maximum level: one range block covers both files.
+        assert_eq!(report.recursion_level, 3);
+        assert_eq!(report.blocks.len(), 1);
+        assert_eq!(report.blocks[0].differing_bytes, Some(500));
+        assert_eq!(report.runs.len(), 3);
+        assert_eq!(report.differing_bytes, 500);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let data0 = vec![0u8; 100];
+        let mut data1 = vec![0u8; 98];
+        data1[5] = 1;
+
+        // One differing byte, and two bytes that only exist in `data0`.
+        let report = DiffReport::new(&data0, &data1, 0, usize::MAX, 4);
+        assert_eq!(report.size_difference, -2);
+        assert_eq!(report.total_difference(), 3);
+        assert!(report.exceeds(0));
+        assert!(report.exceeds(2));
+        assert!(!report.exceeds(3));
+
+        let same = DiffReport::new(&data0, &data0, 0, usize::MAX, 4);
+        assert_eq!(same.total_difference(), 0);
+        assert!(!same.exceeds(0));
+    }
+}
@@ -0,0 +1,300 @@
+//! Byte value distributions and randomness statistics of arbitrary byte ranges.
+//!
+//! `ByteDistribution`s of adjacent ranges can be merged, so the distribution of a large
+//! range can be assembled from cached range blocks (see `range_distribution`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{max_recursion_level, Cacheable, CompleteLargestRangeBlockIterator, RangeBlockCache};
+
+/// A 256-bin byte histogram, with enough extra information to calculate serial correlation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteDistribution {
+    /// The number of bytes of each value.
+    // (serde doesn't derive for arrays this large.)
+    #[serde(with = "counts_serde")]
+    pub counts: [u64; 256],
+    /// Sum of the products of each pair of adjacent bytes.
+    pub pair_product_sum: u64,
+    pub first: Option<u8>,
+    pub last: Option<u8>,
+}
+
+impl Default for ByteDistribution {
+    fn default() -> Self {
+        Self {
+            counts: [0; 256],
+            pair_product_sum: 0,
+            first: None,
+            last: None,
+        }
+    }
+}
+
+impl ByteDistribution {
+    pub fn new(data: &[u8]) -> Self {
+        let mut counts = [0u64; 256];
+        for &byte in data {
+            counts[byte as usize] += 1;
+        }
+
+        Self {
+            counts,
+            pair_product_sum: data
+                .windows(2)
+                .map(|pair| pair[0] as u64 * pair[1] as u64)
+                .sum(),
+            first: data.first().copied(),
+            last: data.last().copied(),
+        }
+    }
+
+    /// Combines the distribution of a byte range with that of the range that immediately follows it.
+    pub fn merge(&self, next: &Self) -> Self {
+        let mut counts = self.counts;
+        for (count, next) in counts.iter_mut().zip(next.counts) {
+            *count += next;
+        }
+
+        let boundary_pair = match (self.last, next.first) {
+            (Some(last), Some(first)) => last as u64 * first as u64,
+            _ => 0,
+        };
+
+        Self {
+            counts,
+            pair_product_sum: self.pair_product_sum + next.pair_product_sum + boundary_pair,
+            first: self.first.or(next.first),
+            last: next.last.or(self.last),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first.is_none()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let sum: f64 = self.weighted_sum(|byte| byte);
+        sum / self.len() as f64
+    }
+
+    /// Shannon entropy, in bits per byte (0.0 to 8.0).
+    ///
+    /// ```
+    /// use range_blocks::distribution::ByteDistribution;
+    ///
+    /// assert_eq!(ByteDistribution::new(&[9; 100]).entropy(), 0.0);
+    /// assert_eq!(ByteDistribution::new(&[0, 1, 2, 3]).entropy(), 2.0);
+    /// ```
+    pub fn entropy(&self) -> f64 {
+        let len = self.len() as f64;
+        self.counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / len;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Pearson's chi-square statistic against a uniform distribution of byte values
+    /// (255 degrees of freedom).
+    pub fn chi_square(&self) -> f64 {
+        let expected = self.len() as f64 / 256.0;
+        self.counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    /// Serial correlation coefficient of each byte with the next, wrapping around from the last
+    /// byte to the first (as in the `ent` tool): near 0.0 for random data. `None` if undefined,
+    /// i.e. if every byte has the same value.
+    pub fn serial_correlation(&self) -> Option<f64> {
+        let (first, last) = (self.first?, self.last?);
+        let n = self.len() as f64;
+        let pair_sum = (self.pair_product_sum + last as u64 * first as u64) as f64;
+        let sum = self.weighted_sum(|byte| byte);
+        let square_sum = self.weighted_sum(|byte| byte * byte);
+
+        let denominator = n * square_sum - sum * sum;
+        (denominator != 0.0).then(|| (n * pair_sum - sum * sum) / denominator)
+    }
+
+    /// The `n` most common byte values and their counts, most common first.
+    pub fn most_common(&self, n: usize) -> Vec<(u8, u64)> {
+        let mut counts: Vec<(u8, u64)> = (0..=255)
+            .zip(self.counts)
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Sum of `f(byte value)` over every byte.
+    fn weighted_sum(&self, f: impl Fn(f64) -> f64) -> f64 {
+        (0..=255u8)
+            .zip(self.counts)
+            .map(|(byte, count)| f(byte as f64) * count as f64)
+            .sum()
+    }
+}
+
+/// `RangeBlockDistribution` is a `Cacheable` implementor that allows cached access to the
+/// `ByteDistribution` of a range block.
+pub struct RangeBlockDistribution<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RangeBlockDistribution<'a> {
+    /// Identifies caches of `ByteDistribution`s in saved cache files:
+    /// change this whenever `ByteDistribution` changes.
+    pub const CACHE_ID: &'static str = "distribution/1";
+
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl Cacheable<ByteDistribution> for RangeBlockDistribution<'_> {
+    fn value(&self, index: u64, count: u64) -> ByteDistribution {
+        let limit =
+            usize::try_from((self.data.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let index = usize::try_from(index).unwrap_or(usize::MAX);
+
+        self.data
+            .get(index..limit)
+            .map(ByteDistribution::new)
+            .unwrap_or_default()
+    }
+
+    fn value_from_sub_blocks(&self, value: &[ByteDistribution]) -> ByteDistribution {
+        value
+            .iter()
+            .fold(ByteDistribution::default(), |distribution, sub_block| {
+                distribution.merge(sub_block)
+            })
+    }
+
+    /// Each `ByteDistribution` is over 2 KB: only cache range blocks of 1 MB (with 4x4 sub-blocks) or more.
+    fn min_cached_recursion_level(&self) -> u32 {
+        5
+    }
+}
+
+/// The `ByteDistribution` of `len` bytes of `data` starting at `index`, assembled from
+/// the largest range blocks in that range: `cache` should be generated from
+/// `RangeBlockDistribution::new(data)`, or be empty.
+///
+/// ```
+/// use range_blocks::distribution::{range_distribution, ByteDistribution, RangeBlockDistribution};
+/// use range_blocks::RangeBlockCache;
+///
+/// let data: Vec<u8> = (0..5_000_000u32).map(|i| (i % 251) as u8).collect();
+/// let cache = RangeBlockCache::generate(&RangeBlockDistribution::new(&data), data.len(), 4);
+///
+/// let distribution = range_distribution(&data, &cache, 1000, 4_000_000, 4);
+/// assert_eq!(distribution, ByteDistribution::new(&data[1000..4_001_000]));
+/// ```
+pub fn range_distribution(
+    data: &[u8],
+    cache: &RangeBlockCache<ByteDistribution>,
+    index: u64,
+    len: u64,
+    sub_block_sqrt: u64,
+) -> ByteDistribution {
+    let data_len = data.len() as u64;
+    let end = data_len.min(index.saturating_add(len));
+    let distribution = RangeBlockDistribution::new(data);
+
+    CompleteLargestRangeBlockIterator::new(
+        index.min(end),
+        end,
+        max_recursion_level(data_len, sub_block_sqrt),
+        sub_block_sqrt,
+    )
+    .map(|(index, count)| {
+        cache
+            .get(index, count)
+            .unwrap_or_else(|| distribution.value(index, count))
+    })
+    .fold(ByteDistribution::default(), |distribution, block| {
+        distribution.merge(&block)
+    })
+}
+
+mod counts_serde {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(counts: &[u64; 256], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(counts)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u64; 256], D::Error> {
+        let counts = Vec::<u64>::deserialize(deserializer)?;
+        counts
+            .try_into()
+            .map_err(|counts: Vec<u64>| D::Error::invalid_length(counts.len(), &"256 counts"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn statistics() {
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        let uniform = ByteDistribution::new(&all_bytes.repeat(4));
+        assert_eq!(uniform.len(), 1024);
+        assert_eq!(uniform.mean(), 127.5);
+        assert_eq!(uniform.entropy(), 8.0);
+        assert_eq!(uniform.chi_square(), 0.0);
+
+        let constant = ByteDistribution::new(&[0x42; 10]);
+        assert_eq!(constant.serial_correlation(), None);
+        assert_eq!(constant.most_common(3), vec![(0x42, 10)]);
+
+        // Alternating values are perfectly anti-correlated.
+        let alternating = ByteDistribution::new(&[0, 255].repeat(50));
+        assert_eq!(alternating.serial_correlation(), Some(-1.0));
+
+        let mut rng = rand::thread_rng();
+        let random: Vec<u8> = (0..1_000_000).map(|_| rng.gen()).collect();
+        let random = ByteDistribution::new(&random);
+        assert!(random.serial_correlation().unwrap().abs() < 0.01);
+        assert!(random.entropy() > 7.99);
+    }
+
+    #[test]
+    fn merged_distributions() {
+        let mut rng = rand::thread_rng();
+        let data: Vec<u8> = (0..3000).map(|_| rng.gen()).collect();
+
+        for split in [0, 1, 1500, 2999, 3000] {
+            let (a, b) = data.split_at(split);
+            assert_eq!(
+                ByteDistribution::new(a).merge(&ByteDistribution::new(b)),
+                ByteDistribution::new(&data)
+            );
+        }
+
+        let empty = RangeBlockCache::new();
+        assert_eq!(
+            range_distribution(&data, &empty, 17, 2000, 4),
+            ByteDistribution::new(&data[17..2017])
+        );
+        // Clamped to the end of the data
+        assert_eq!(
+            range_distribution(&data, &empty, 2500, 2000, 4),
+            ByteDistribution::new(&data[2500..])
+        );
+    }
+}
@@ -0,0 +1,865 @@
+//! A *Range Block* is square graphical representation of a contiguous block of memory.
+//! The smallest size of range block is called a *Cell*: it contains a single byte.
+//! Larger range blocks encompass a square number (normally 16) of smaller ones:
+//! one level of *recursion* is the representation of a range with one more such
+//! encompassing step.
+//!
+//! A range block with a recursion level of 0 contains 1 cell.
+//! Range block start indices are always aligned such that
+//! 0 == *start index* % *block size in bytes (or cells)*.
+//!
+//! This crate has no GUI dependencies: it provides the range block layout,
+//! iterators over range blocks, and `RangeBlockCache` for fast lookup of
+//! `Cacheable` functions on range block contents.
+//! Optional features add multi-threaded cache generation (`parallel`),
+//! checksums and hashes (`checksum`), and PNG and SVG images (`render`).
+//!
+//! ```
+//! use range_blocks::{range_block_size, RangeBlockCache, RangeBlockSum, DEFAULT_SUB_BLOCK_SQRT};
+//!
+//! let data = vec![1u8; 4096];
+//! let cache = RangeBlockCache::generate(&RangeBlockSum::new(&data), data.len(), DEFAULT_SUB_BLOCK_SQRT);
+//!
+//! // The whole file is one recursion level 3 range block.
+//! assert_eq!(range_block_size(3, DEFAULT_SUB_BLOCK_SQRT), 4096);
+//! assert_eq!(cache.get(0, 4096), Some(4096));
+//! ```
+
+pub mod cache_file;
+#[cfg(feature = "checksum")]
+pub mod checksum;
+pub mod color;
+pub mod diff;
+pub mod diff_report;
+pub mod distribution;
+pub mod range_border;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod stats;
+pub mod strings;
+#[cfg(feature = "render")]
+pub mod svg;
+pub mod utilities;
+
+/// The usual number of sub-blocks along each side of a range block:
+/// each range block contains 4x4 = 16 sub-blocks.
+pub const DEFAULT_SUB_BLOCK_SQRT: u64 = 4;
+
+/// Integer coordinate units for drawing cells and range blocks
+/// in a two-dimensional rendering scheme. A cell is a single-byte block and has
+/// a nominal size of 1x1, as measured in `CellCoords`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellCoords {
+    pub x: u64,
+    pub y: u64,
+}
+
+/// The `CellCoords` of the minimum (top-left) corner of the `index` byte's cell.
+///
+/// ```
+/// use range_blocks::{get_cell_offset, CellCoords};
+///
+/// assert_eq!(get_cell_offset(0, 4), CellCoords { x: 0, y: 0 });
+/// assert_eq!(get_cell_offset(5, 4), CellCoords { x: 1, y: 1 });
+/// assert_eq!(get_cell_offset(16, 4), CellCoords { x: 4, y: 0 });
+/// ```
+pub fn get_cell_offset(index: u64, sub_block_sqrt: u64) -> CellCoords {
+    let sub_block_count = sub_block_sqrt * sub_block_sqrt;
+    let (mut x, mut y) = (0, 0);
+    let mut index = index;
+    let mut scale = 1u64;
+
+    while index > 0 {
+        let sub_block_index = index % sub_block_count;
+
+        x += (sub_block_index % sub_block_sqrt) * scale;
+        y += (sub_block_index / sub_block_sqrt) * scale;
+
+        index /= sub_block_count;
+        scale *= sub_block_sqrt;
+    }
+
+    CellCoords { x, y }
+}
+
+/// The index of the byte whose cell is at `cell` (the inverse of `get_cell_offset`).
+///
+/// ```
+/// use range_blocks::{get_cell_index, CellCoords};
+///
+/// assert_eq!(get_cell_index(CellCoords { x: 1, y: 1 }, 4), 5);
+/// assert_eq!(get_cell_index(CellCoords { x: 4, y: 0 }, 4), 16);
+/// ```
+pub fn get_cell_index(cell: CellCoords, sub_block_sqrt: u64) -> u64 {
+    let sub_block_count = sub_block_sqrt * sub_block_sqrt;
+    let CellCoords { mut x, mut y } = cell;
+    let mut index = 0;
+    let mut scale = 1u64;
+
+    while x > 0 || y > 0 {
+        index += ((y % sub_block_sqrt) * sub_block_sqrt + x % sub_block_sqrt) * scale;
+
+        x /= sub_block_sqrt;
+        y /= sub_block_sqrt;
+        scale *= sub_block_count;
+    }
+
+    index
+}
+
+/// Calculate the top-left and bottom-right corners of a range block.
+/// Note: `index` and `count` should specify a real square range block,
+/// otherwise the result may not be what you expect.
+///
+/// ```
+/// use range_blocks::{range_block_corners, CellCoords};
+///
+/// assert_eq!(
+///     range_block_corners(16, 16, 4),
+///     (CellCoords { x: 4, y: 0 }, CellCoords { x: 8, y: 4 })
+/// );
+/// ```
+pub fn range_block_corners(
+    index: u64,
+    count: u64,
+    sub_block_sqrt: u64,
+) -> (CellCoords, CellCoords) {
+    let top_left = get_cell_offset(index, sub_block_sqrt);
+
+    let bottom_right = get_cell_offset(index + count - 1, sub_block_sqrt);
+    let bottom_right = CellCoords {
+        x: bottom_right.x + 1,
+        y: bottom_right.y + 1,
+    };
+
+    (top_left, bottom_right)
+}
+
+/// The byte size of a range block at a recursion level.
+///
+/// ```
+/// use range_blocks::range_block_size;
+///
+/// assert_eq!(range_block_size(0, 4), 1);
+/// assert_eq!(range_block_size(2, 4), 256);
+/// ```
+pub fn range_block_size(recursion_level: u32, sub_block_sqrt: u64) -> u64 {
+    sub_block_sqrt.pow(2 * recursion_level)
+}
+
+/// The maximum recursion level needed for this data length:
+/// the lowest recursion level that contains at least `data_len` cells.
+///
+/// ```
+/// use range_blocks::max_recursion_level;
+///
+/// assert_eq!(max_recursion_level(256, 4), 2);
+/// assert_eq!(max_recursion_level(257, 4), 3);
+/// ```
+pub fn max_recursion_level(data_len: u64, sub_block_sqrt: u64) -> u32 {
+    (data_len as f32)
+        .log((sub_block_sqrt * sub_block_sqrt) as f32)
+        .ceil() as u32
+}
+
+/// Finds the next range block at the target recursion level. Range blocks at or above
+/// `target_recursion_level` that are encountered during the search will be tested with
+/// `fn_filter`: if it returns `false`, the entire range block will be skipped.
+///
+/// This filter system is used to efficiently draw only the range blocks
+/// that are currently visible in the UI view window.
+///
+/// ```
+/// use range_blocks::next_range_block;
+///
+/// // The first level 1 range block at or after index 20, skipping the range block at 32.
+/// let next = next_range_block(20, 256, 1, 2, 4, |index, _| index != 32);
+/// assert_eq!(next, Some((48, 16)));
+/// ```
+pub fn next_range_block(
+    search_start_index: u64,
+    data_len: u64,
+    target_recursion_level: u32,
+    max_recursion_level: u32,
+    sub_block_sqrt: u64,
+    mut fn_filter: impl FnMut(u64, u64) -> bool,
+) -> Option<(u64, u64)> {
+    assert!(sub_block_sqrt > 1);
+
+    let mut search_start_index = search_start_index;
+    let mut max_recursion_level = max_recursion_level;
+    let target_alignment = range_block_size(target_recursion_level, sub_block_sqrt);
+
+    //Note:
+    // "recursion level" refers to the number of times that sub-blocks are
+    // grouped together into blocks.
+    // Recursion level zero means no grouping: just individual bytes.
+
+    loop {
+        //assert!(search_start_index < data_len);
+        assert!(target_recursion_level <= max_recursion_level);
+
+        // Find the next index aligned with blocks at the target recursion level.
+        let next_aligned_index = search_start_index.next_multiple_of(target_alignment);
+        if next_aligned_index >= data_len {
+            return None;
+        }
+
+        // Are there any higher recursion levels (larger blocks) that also align here?
+        let max_aligned_recursion_level = (target_recursion_level..=max_recursion_level)
+            .rev()
+            .find(|i| 0 == next_aligned_index % range_block_size(*i, sub_block_sqrt))
+            .expect("there should be an aligned recursion level");
+
+        let max_aligned_recursion_level_block_size =
+            range_block_size(max_aligned_recursion_level, sub_block_sqrt);
+
+        // An aligned index is always at the start of at least one recursion block:
+        //      Find the biggest one, and then
+        //          return it (the target recursion level),
+        //          recurse into it (bigger than our target),
+        //          or skip it (filtered out)
+        if fn_filter(next_aligned_index, max_aligned_recursion_level_block_size) {
+            if max_aligned_recursion_level == target_recursion_level {
+                return Some((next_aligned_index, max_aligned_recursion_level_block_size));
+            }
+            max_recursion_level -= 1;
+        } else {
+            search_start_index = next_aligned_index + max_aligned_recursion_level_block_size;
+        }
+    }
+}
+
+///`Iterator` over range blocks at the target recursion level.
+/// Uses `next_range_block`: see it for more details.
+///
+/// ```
+/// use range_blocks::RangeBlockIterator;
+///
+/// let blocks: Vec<_> = RangeBlockIterator::new(0, 40, 1, 2, 4, |_, _| true).collect();
+/// assert_eq!(blocks, vec![(0, 16), (16, 16), (32, 16)]);
+/// ```
+pub struct RangeBlockIterator<'a> {
+    search_start_index: u64,
+    data_len: u64,
+    target_recursion_level: u32,
+    max_recursion_level: u32,
+    sub_block_sqrt: u64,
+    fn_filter: Box<dyn FnMut(u64, u64) -> bool + 'a>,
+}
+
+impl<'a> RangeBlockIterator<'a> {
+    pub fn new(
+        search_start_index: u64,
+        data_len: u64,
+        target_recursion_level: u32,
+        max_recursion_level: u32,
+        sub_block_sqrt: u64,
+        fn_filter: impl FnMut(u64, u64) -> bool + 'a,
+    ) -> Self {
+        Self {
+            search_start_index,
+            data_len,
+            target_recursion_level,
+            max_recursion_level,
+            sub_block_sqrt,
+            fn_filter: Box::new(fn_filter),
+        }
+    }
+}
+
+impl Iterator for RangeBlockIterator<'_> {
+    type Item = (u64, u64);
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((index, count)) = next_range_block(
+            self.search_start_index,
+            self.data_len,
+            self.target_recursion_level,
+            self.max_recursion_level,
+            self.sub_block_sqrt,
+            &mut self.fn_filter,
+        ) {
+            self.search_start_index = index + count;
+            Some((index, count))
+        } else {
+            None
+        }
+    }
+}
+
+/// Find the largest (highest recursion level) range block that
+/// starts at `index` and ends before `limit_index`.
+pub fn next_complete_largest_range_block(
+    index: u64,
+    limit_index: u64,
+    max_recursion_level: u32,
+    sub_block_sqrt: u64,
+) -> Option<(u64, u64)> {
+    assert!(sub_block_sqrt > 1);
+
+    //Note:
+    // "recursion level" refers to the number of times that sub-blocks are
+    // grouped together into blocks.
+    // Recursion level zero means no grouping: just individual bytes.
+
+    // Find the largest range block that
+    // - starts at index
+    // - ends before limit_index
+
+    (0..=max_recursion_level)
+        .rev()
+        .map(|i| range_block_size(i, sub_block_sqrt))
+        .filter(|&size| 0 == index % size)
+        .filter(|&size| index + size <= limit_index)
+        .map(|size| (index, size))
+        .next()
+}
+
+/// `Iterator` which produces the largest range blocks that fill a range.
+/// Uses `next_complete_largest_range_block`: see it for more details.
+pub struct CompleteLargestRangeBlockIterator {
+    search_start_index: u64,
+    search_end_index: u64,
+    max_recursion_level: u32,
+    sub_block_sqrt: u64,
+}
+
+impl CompleteLargestRangeBlockIterator {
+    pub fn new(
+        search_start_index: u64,
+        search_end_index: u64,
+        max_recursion_level: u32,
+        sub_block_sqrt: u64,
+    ) -> Self {
+        Self {
+            search_start_index,
+            search_end_index,
+            max_recursion_level,
+            sub_block_sqrt,
+        }
+    }
+}
+
+impl Iterator for CompleteLargestRangeBlockIterator {
+    type Item = (u64, u64);
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((index, count)) = next_complete_largest_range_block(
+            self.search_start_index,
+            self.search_end_index,
+            self.max_recursion_level,
+            self.sub_block_sqrt,
+        ) {
+            self.search_start_index = index + count;
+            Some((index, count))
+        } else {
+            None
+        }
+    }
+}
+
+/// `Cacheable` functions on range blocks can be stored in a `RangeBlockCache`.
+///
+/// ```
+/// use range_blocks::{Cacheable, RangeBlockCache};
+///
+/// /// The largest byte in a range block.
+/// struct RangeBlockMax<'a>(&'a [u8]);
+///
+/// impl Cacheable<u8> for RangeBlockMax<'_> {
+///     fn value(&self, index: u64, count: u64) -> u8 {
+///         let start = self.0.len().min(index as usize);
+///         let end = self.0.len().min((index + count) as usize);
+///         self.0[start..end].iter().copied().max().unwrap_or(0)
+///     }
+///
+///     fn value_from_sub_blocks(&self, value: &[u8]) -> u8 {
+///         value.iter().copied().max().unwrap_or(0)
+///     }
+/// }
+///
+/// let mut data = vec![0u8; 1000];
+/// data[700] = 9;
+/// let cache = RangeBlockCache::generate(&RangeBlockMax(&data), data.len(), 4);
+/// assert_eq!(cache.get(0, 4096), Some(9));
+/// assert_eq!(cache.get(0, 256), Some(0));
+/// ```
+pub trait Cacheable<T> {
+    /// The value for the range block at `index` containing `count` cells, calculated directly.
+    fn value(&self, index: u64, count: u64) -> T;
+    /// The value for a range block, calculated from the values of its sub-blocks.
+    fn value_from_sub_blocks(&self, value: &[T]) -> T;
+    /// The lowest recursion level that `RangeBlockCache` stores:
+    /// lower levels should be quick enough to calculate on demand.
+    // Note: this works fine for sub_block_sqrt = 4; replace hardcode later?
+    fn min_cached_recursion_level(&self) -> u32 {
+        2
+    }
+}
+
+/// `RangeBlockSum` is a `Cacheable` implementor that allows cached access to the sum
+/// of bytes in a range block.
+pub struct RangeBlockSum<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RangeBlockSum<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn block_sum(&self, index: u64, count: u64) -> u64 {
+        let limit =
+            usize::try_from((self.data.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let index = usize::try_from(index).unwrap_or(usize::MAX);
+
+        if index < self.data.len() {
+            self.data[index..limit].iter().map(|&x| x as u64).sum()
+        } else {
+            0
+        }
+    }
+}
+
+impl Cacheable<u64> for RangeBlockSum<'_> {
+    fn value(&self, index: u64, count: u64) -> u64 {
+        self.block_sum(index, count)
+    }
+
+    fn value_from_sub_blocks(&self, value: &[u64]) -> u64 {
+        value.iter().sum()
+    }
+}
+
+/// `RangeBlockColorSum` is a `Cacheable` implementor that allows cached access to the sum
+/// of the RGB color channels of every cell in a range block, according to some cell coloring scheme.
+pub struct RangeBlockColorSum<'a, 'b> {
+    data: &'a [u8],
+    color_fn: Box<dyn Fn(u8) -> (u64, u64, u64) + Send + Sync + 'b>,
+}
+
+impl<'a, 'b> RangeBlockColorSum<'a, 'b> {
+    pub fn new(
+        data: &'a [u8],
+        color_fn: impl Fn(u8) -> (u64, u64, u64) + Send + Sync + 'b,
+    ) -> Self {
+        Self {
+            data,
+            color_fn: Box::new(color_fn),
+        }
+    }
+
+    pub fn block_color_sum(&self, index: u64, count: u64) -> (u64, u64, u64) {
+        let limit =
+            usize::try_from((self.data.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let index = usize::try_from(index).unwrap_or(usize::MAX);
+
+        let (mut sum_r, mut sum_g, mut sum_b) = (0, 0, 0);
+
+        if index < self.data.len() {
+            for (r, g, b) in self.data[index..limit].iter().map(|&x| (self.color_fn)(x)) {
+                sum_r += r;
+                sum_g += g;
+                sum_b += b;
+            }
+            (sum_r, sum_g, sum_b)
+        } else {
+            (0, 0, 0)
+        }
+    }
+}
+
+impl Cacheable<(u64, u64, u64)> for RangeBlockColorSum<'_, '_> {
+    fn value(&self, index: u64, count: u64) -> (u64, u64, u64) {
+        self.block_color_sum(index, count)
+    }
+
+    fn value_from_sub_blocks(&self, value: &[(u64, u64, u64)]) -> (u64, u64, u64) {
+        value
+            .iter()
+            .fold((0, 0, 0), |(sum_r, sum_g, sum_b), (r, g, b)| {
+                (sum_r + r, sum_g + g, sum_b + b)
+            })
+    }
+}
+
+/// `RangeBlockDiff` is a `Cacheable` implementor that allows cached access to the total count of
+/// byte indices within a range block in which the index has different byte values between two files.
+pub struct RangeBlockDiff<'a> {
+    data0: &'a [u8],
+    data1: &'a [u8],
+}
+
+impl<'a> RangeBlockDiff<'a> {
+    pub fn new(data0: &'a [u8], data1: &'a [u8]) -> Self {
+        Self { data0, data1 }
+    }
+
+    pub fn block_diff(&self, index: u64, count: u64) -> Option<usize> {
+        let limit0 =
+            usize::try_from((self.data0.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let limit1 =
+            usize::try_from((self.data1.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let limit = std::cmp::min(limit0, limit1);
+        let index = usize::try_from(index).unwrap_or(usize::MAX);
+        let data_len = std::cmp::min(self.data0.len(), self.data1.len());
+
+        if index < data_len {
+            Some(
+                self.data0[index..limit]
+                    .iter()
+                    .zip(self.data1[index..limit].iter())
+                    .filter(|(a, b)| a != b)
+                    .count(),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl Cacheable<Option<usize>> for RangeBlockDiff<'_> {
+    fn value(&self, index: u64, count: u64) -> Option<usize> {
+        self.block_diff(index, count)
+    }
+
+    fn value_from_sub_blocks(&self, value: &[Option<usize>]) -> Option<usize> {
+        // Blocks entirely past the end of either file have nothing to compare.
+        value.iter().flatten().copied().reduce(|a, b| a + b)
+    }
+}
+
+/// `RangeBlockVariability` is a `Cacheable` implementor that allows cached access to the set of files
+/// whose contents differ from a reference file within a range block.
+/// The set is a bitmask: bit `i` is set IFF file `i` differs from the reference file.
+/// Bytes past the end of a file count as different from bytes that exist in the reference file.
+pub struct RangeBlockVariability<'a> {
+    files: Vec<&'a [u8]>,
+    reference: usize,
+}
+
+impl<'a> RangeBlockVariability<'a> {
+    /// The maximum number of files that can be compared (one bit per file).
+    pub const MAX_FILES: usize = u64::BITS as usize;
+
+    pub fn new(files: Vec<&'a [u8]>, reference: usize) -> Self {
+        assert!(files.len() <= Self::MAX_FILES);
+        assert!(reference < files.len());
+        Self { files, reference }
+    }
+
+    pub fn block_variability(&self, index: u64, count: u64) -> u64 {
+        let block = |data: &'a [u8]| -> &'a [u8] {
+            let limit =
+                usize::try_from((data.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+            let index = usize::try_from(index).unwrap_or(usize::MAX);
+
+            data.get(index..limit).unwrap_or(&[])
+        };
+
+        let reference_block = block(self.files[self.reference]);
+
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|&(_, &data)| block(data) != reference_block)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+}
+
+impl Cacheable<u64> for RangeBlockVariability<'_> {
+    fn value(&self, index: u64, count: u64) -> u64 {
+        self.block_variability(index, count)
+    }
+
+    fn value_from_sub_blocks(&self, value: &[u64]) -> u64 {
+        value.iter().fold(0, |mask, sub_mask| mask | sub_mask)
+    }
+}
+
+/// Uses `Cacheable` implementors to cache functions on range block contents.
+/// This is used to provide fast lookup for
+/// * the sum of byte values in a range block
+/// * the byte difference count between the same range block in two loaded files
+/// * and other things
+///
+/// Range blocks at each recursion level are aligned and contiguous, so each cached level
+/// is stored as a flat `Vec`, indexed by `index / block size`.
+pub struct RangeBlockCache<T: Clone> {
+    /// See `Cacheable::min_cached_recursion_level`.
+    min_recursion_level: u32,
+    /// The block size and range block values of each cached recursion level, lowest first.
+    levels: Vec<(u64, Vec<T>)>,
+}
+
+impl<T: Clone> Default for RangeBlockCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> RangeBlockCache<T> {
+    pub fn new() -> Self {
+        Self {
+            min_recursion_level: 0,
+            levels: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, index: u64, count: u64) -> Option<T> {
+        let (_, values) = self
+            .levels
+            .iter()
+            .find(|(block_size, _)| *block_size == count)?;
+
+        if index % count != 0 {
+            return None;
+        }
+
+        values.get(usize::try_from(index / count).ok()?).cloned()
+    }
+
+    /// The number of bytes used to store cached values.
+    pub fn memory_usage(&self) -> usize {
+        self.levels
+            .iter()
+            .map(|(_, values)| values.capacity() * std::mem::size_of::<T>())
+            .sum()
+    }
+
+    /// Generates a cache for `cacheable`. The lowest recursion levels are skipped to save storage space;
+    /// they can be calculated quickly on demand.
+    pub fn generate(cacheable: &impl Cacheable<T>, data_len: usize, sub_block_sqrt: u64) -> Self {
+        let data_len: u64 = data_len.try_into().expect("data_len should fit in u64");
+        let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+
+        log::info!("max_recursion_level: {:?}", max_recursion_level);
+
+        let mut levels: Vec<(u64, Vec<T>)> = Vec::new();
+
+        let min_recursion_level = cacheable.min_cached_recursion_level();
+        for i in min_recursion_level..=max_recursion_level {
+            let block_size = range_block_size(i, sub_block_sqrt);
+            let block_count = data_len.div_ceil(block_size);
+
+            let level_values = (0..block_count)
+                .map(|k| Self::block_value(cacheable, levels.last(), k, block_size, sub_block_sqrt))
+                .collect();
+
+            levels.push((block_size, level_values));
+        }
+
+        Self::log_levels(&levels);
+
+        Self {
+            min_recursion_level,
+            levels,
+        }
+    }
+
+    /// Multi-threaded version of `generate`, with the same result.
+    ///
+    /// Each recursion level is computed in parallel, from the level below it.
+    #[cfg(feature = "parallel")]
+    pub fn generate_parallel(
+        cacheable: &(impl Cacheable<T> + Sync),
+        data_len: usize,
+        sub_block_sqrt: u64,
+    ) -> Self
+    where
+        T: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let data_len: u64 = data_len.try_into().expect("data_len should fit in u64");
+        let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+
+        log::info!("max_recursion_level: {:?}", max_recursion_level);
+
+        let mut levels: Vec<(u64, Vec<T>)> = Vec::new();
+
+        let min_recursion_level = cacheable.min_cached_recursion_level();
+        for i in min_recursion_level..=max_recursion_level {
+            let block_size = range_block_size(i, sub_block_sqrt);
+            let block_count = data_len.div_ceil(block_size);
+
+            let sub_level = levels.last();
+            let level_values = (0..block_count)
+                .into_par_iter()
+                .map(|k| Self::block_value(cacheable, sub_level, k, block_size, sub_block_sqrt))
+                .collect();
+
+            levels.push((block_size, level_values));
+        }
+
+        Self::log_levels(&levels);
+
+        Self {
+            min_recursion_level,
+            levels,
+        }
+    }
+
+    /// The value of the `k`th range block of size `block_size`: calculated directly
+    /// at the lowest cached level, otherwise from the cached values of `sub_level`.
+    fn block_value(
+        cacheable: &impl Cacheable<T>,
+        sub_level: Option<&(u64, Vec<T>)>,
+        k: u64,
+        block_size: u64,
+        sub_block_sqrt: u64,
+    ) -> T {
+        let Some((sub_block_size, sub_values)) = sub_level else {
+            return cacheable.value(k * block_size, block_size);
+        };
+
+        let sub_block_count = sub_block_sqrt * sub_block_sqrt;
+        let sub_accumulator: Vec<T> = (k * sub_block_count..(k + 1) * sub_block_count)
+            .map(|sub_k| {
+                // Sub-blocks past the end of the data are not in the level below.
+                usize::try_from(sub_k)
+                    .ok()
+                    .and_then(|sub_k| sub_values.get(sub_k))
+                    .cloned()
+                    .unwrap_or_else(|| cacheable.value(sub_k * sub_block_size, *sub_block_size))
+            })
+            .collect();
+
+        cacheable.value_from_sub_blocks(&sub_accumulator)
+    }
+
+    /// Does this cache have the shape of one generated for `data_len` bytes?
+    fn is_valid_for(&self, data_len: u64, sub_block_sqrt: u64) -> bool {
+        if sub_block_sqrt < 2 {
+            return false;
+        }
+        let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+        let expected_levels = (self.min_recursion_level..=max_recursion_level).map(|i| {
+            let block_size = range_block_size(i, sub_block_sqrt);
+            (block_size, data_len.div_ceil(block_size))
+        });
+
+        self.levels
+            .iter()
+            .map(|(block_size, values)| (*block_size, values.len() as u64))
+            .eq(expected_levels)
+    }
+
+    fn log_levels(levels: &[(u64, Vec<T>)]) {
+        for (block_size, values) in levels {
+            log::info!("block size {block_size}: {} values", values.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_get() {
+        let data: Vec<u8> = (0..70_000).map(|i| i as u8).collect();
+        let sum = RangeBlockSum::new(&data);
+        let cache = RangeBlockCache::generate(&sum, data.len(), 4);
+
+        for recursion_level in 2..=max_recursion_level(data.len() as u64, 4) {
+            let count = range_block_size(recursion_level, 4);
+            for index in (0..data.len() as u64).step_by(count as usize) {
+                assert_eq!(cache.get(index, count), Some(sum.value(index, count)));
+            }
+            // Past the end of the data
+            assert_eq!(cache.get(data.len() as u64 + count, count), None);
+        }
+
+        // Uncached recursion level
+        assert_eq!(cache.get(0, 16), None);
+        // Misaligned
+        assert_eq!(cache.get(128, 256), None);
+    }
+
+    #[test]
+    fn cell_index_inverts_cell_offset() {
+        for sub_block_sqrt in [2, 4] {
+            for index in 0..10_000 {
+                let cell = get_cell_offset(index, sub_block_sqrt);
+                assert_eq!(get_cell_index(cell, sub_block_sqrt), index);
+            }
+        }
+    }
+
+    #[test]
+    fn variability() {
+        let reference = [0u8; 256];
+        let mut differs = reference;
+        differs[100] = 1;
+        let shorter = [0u8; 200];
+        let files: Vec<&[u8]> = vec![&differs, &reference, &shorter, &reference];
+        let variability = RangeBlockVariability::new(files, 1);
+
+        // The reference file's own bit (1) is never set.
+        assert_eq!(variability.value(0, 16), 0);
+        assert_eq!(variability.value(96, 16), 0b0001);
+        // Bytes past the end of a file differ.
+        assert_eq!(variability.value(192, 16), 0b0100);
+        assert_eq!(variability.value(0, 256), 0b0101);
+
+        // Sub-block masks combine to the block's mask.
+        let cache = RangeBlockCache::generate(&variability, 256, 4);
+        assert_eq!(cache.get(0, 256), Some(0b0101));
+
+        // One bit for each of `MAX_FILES` files.
+        let mut files: Vec<&[u8]> = vec![&differs; RangeBlockVariability::MAX_FILES];
+        files[0] = &reference;
+        let variability = RangeBlockVariability::new(files, 0);
+        assert_eq!(variability.value(96, 16), !1);
+        assert_eq!(variability.value(96, 16).count_ones(), u64::BITS - 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn variability_too_many_files() {
+        let data = [0u8; 16];
+        RangeBlockVariability::new(vec![&data; RangeBlockVariability::MAX_FILES + 1], 0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn generate_parallel_matches_generate() {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+
+        // Random cached blocks of the parallel cache against values calculated directly.
+        fn check<T: Clone + PartialEq + Send + Sync + std::fmt::Debug>(
+            cacheable: &(impl Cacheable<T> + Sync),
+            data_len: usize,
+            rng: &mut impl Rng,
+        ) {
+            let cache = RangeBlockCache::generate_parallel(cacheable, data_len, 4);
+            assert_eq!(
+                cache.levels,
+                RangeBlockCache::generate(cacheable, data_len, 4).levels
+            );
+
+            let data_len = data_len as u64;
+            for recursion_level in 2..=max_recursion_level(data_len, 4) {
+                let count = range_block_size(recursion_level, 4);
+                for _ in 0..100 {
+                    let index = rng.gen_range(0..data_len.div_ceil(count)) * count;
+                    assert_eq!(
+                        cache.get(index, count),
+                        Some(cacheable.value(index, count)),
+                        "block at {index} of size {count}"
+                    );
+                }
+            }
+        }
+
+        for len in [0, 1, 255, 256, 257, 4096, 70_000] {
+            let data0: Vec<u8> = (0..len).map(|_| rng.gen_range(0..4)).collect();
+            let data1: Vec<u8> = (0..len + 100).map(|_| rng.gen_range(0..4)).collect();
+
+            check(&RangeBlockSum::new(&data0), len, &mut rng);
+            check(&RangeBlockDiff::new(&data0, &data1), len + 100, &mut rng);
+        }
+    }
+}
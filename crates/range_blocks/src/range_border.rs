@@ -1,6 +1,20 @@
+//! Outlines of sets of range blocks.
+//!
+//! ```
+//! use range_blocks::{range_block_corners, range_border::RangeBorder};
+//!
+//! // Two adjacent range blocks share an edge, so their border is a single rectangle.
+//! let mut border = RangeBorder::default();
+//! for index in [0, 16] {
+//!     let (top_left, bottom_right) = range_block_corners(index, 16, 4);
+//!     border.add_rect(top_left, bottom_right);
+//! }
+//! assert_eq!(border.edges.len(), 4);
+//! ```
+
 use std::collections::HashMap;
 
-use crate::range_blocks::CellCoords;
+use crate::CellCoords;
 
 /// A set of `Edge`s that represent the outer border of a set of range blocks.
 #[derive(Default)]
@@ -99,6 +113,8 @@ impl LoopsIter {
         Self { edges }
     }
 
+    // Not `Iterator::next`: each `LoopIter` borrows the remaining edges.
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<LoopIter<'_>> {
         let first_id = self.edges.iter().min_by_key(|&(&x, _)| x).map(|(&x, _)| x);
         if let Some(first_id) = first_id {
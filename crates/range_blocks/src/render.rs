@@ -0,0 +1,371 @@
+//! Headless rendering of range block maps to raster images.
+//!
+//! This draws the same layout as the main view, but on the CPU into a plain RGBA buffer:
+//! no window, GUI context, or GPU is needed. See `svg` for vector output.
+
+use std::fmt;
+use std::io::Write;
+use std::ops::ControlFlow;
+
+use crate::color::{byte_color_rgb, diff_color, semantic01_color_rgb, Rgb};
+use crate::{
+    max_recursion_level, range_block_corners, Cacheable, CellCoords,
+    CompleteLargestRangeBlockIterator, RangeBlockColorSum, RangeBlockDiff, RangeBlockIterator,
+};
+
+/// Limit on rendered image size, to catch accidental multi-gigabyte allocations.
+const MAX_PIXELS: u64 = 1 << 28;
+
+/// How range blocks are colored in a rendered image.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RenderColorMode {
+    Value,
+    Semantic01,
+    Diff,
+}
+
+impl std::str::FromStr for RenderColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "value" => Ok(RenderColorMode::Value),
+            "semantic01" => Ok(RenderColorMode::Semantic01),
+            "diff" => Ok(RenderColorMode::Diff),
+            _ => Err(format!("unknown color mode: {s}")),
+        }
+    }
+}
+
+pub struct RenderOptions {
+    /// Recursion level of the drawn range blocks (clamped to the data's maximum level).
+    pub recursion_level: u32,
+    /// Width and height of each drawn range block, in pixels.
+    pub block_pixels: u32,
+    pub color_mode: RenderColorMode,
+    pub sub_block_sqrt: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RenderError {
+    EmptyData,
+    /// `RenderColorMode::Diff` needs a second file.
+    MissingOtherData,
+    TooLarge {
+        width: u64,
+        height: u64,
+    },
+    /// Too many range blocks for a vector image.
+    TooManyBlocks,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::EmptyData => write!(f, "no data to render"),
+            RenderError::MissingOtherData => write!(f, "diff rendering needs two files"),
+            RenderError::TooLarge { width, height } => write!(
+                f,
+                "{width}x{height} image is too large: use a higher recursion level or fewer block pixels"
+            ),
+            RenderError::TooManyBlocks => write!(
+                f,
+                "too many range blocks for a vector image: use a higher recursion level"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// The region of the cell plane that is drawn into an image, and its scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    /// The cell coordinates of the image's top left corner.
+    pub origin: (f64, f64),
+    /// The width and height of a cell, in pixels.
+    pub cell_pixels: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl View {
+    /// The bounding box of all of the range blocks of `data_len` bytes, with cells
+    /// `cell_pixels` wide.
+    pub fn whole_file(
+        data_len: u64,
+        sub_block_sqrt: u64,
+        cell_pixels: f64,
+    ) -> Result<Self, RenderError> {
+        let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+
+        // The largest range blocks that fill the data cover the same area.
+        let pixel = |cells: u64| (cells as f64 * cell_pixels).round() as u64;
+        let (width, height) = CompleteLargestRangeBlockIterator::new(
+            0,
+            data_len,
+            max_recursion_level,
+            sub_block_sqrt,
+        )
+        .map(|(index, count)| range_block_corners(index, count, sub_block_sqrt).1)
+        .fold((0, 0), |(width, height), bottom_right| {
+            (
+                width.max(pixel(bottom_right.x)),
+                height.max(pixel(bottom_right.y)),
+            )
+        });
+
+        if width * height > MAX_PIXELS {
+            return Err(RenderError::TooLarge { width, height });
+        }
+
+        Ok(Self {
+            origin: (0.0, 0.0),
+            cell_pixels,
+            width: width as u32,
+            height: height as u32,
+        })
+    }
+
+    /// The (unrounded) pixel position of `cell`'s top left corner.
+    pub fn pixel(&self, cell: CellCoords) -> (f64, f64) {
+        (
+            (cell.x as f64 - self.origin.0) * self.cell_pixels,
+            (cell.y as f64 - self.origin.1) * self.cell_pixels,
+        )
+    }
+
+    /// `true` if any of the rectangle between cell corners is in the view.
+    fn shows(&self, top_left: CellCoords, bottom_right: CellCoords) -> bool {
+        let (left, top) = self.pixel(top_left);
+        let (right, bottom) = self.pixel(bottom_right);
+        right > 0.0 && bottom > 0.0 && left < self.width as f64 && top < self.height as f64
+    }
+}
+
+/// Calls `draw(block, top_left, bottom_right)` for each range block (`(index, count)`) of
+/// `data_len` bytes at `recursion_level` that `view` shows, with the block's corners.
+/// The final range block, if incomplete, is drawn as the largest complete range blocks
+/// that fill it (each with the final block's `(index, count)`).
+/// Stops early if `draw` returns `ControlFlow::Break`.
+pub(crate) fn for_each_visible_block(
+    data_len: u64,
+    view: &View,
+    recursion_level: u32,
+    sub_block_sqrt: u64,
+    mut draw: impl FnMut((u64, u64), CellCoords, CellCoords) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+    let is_visible = |index: u64, count: u64| {
+        let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+        view.shows(top_left, bottom_right)
+    };
+
+    for (index, count) in RangeBlockIterator::new(
+        0,
+        data_len,
+        recursion_level.min(max_recursion_level),
+        max_recursion_level,
+        sub_block_sqrt,
+        is_visible,
+    ) {
+        if index + count > data_len {
+            // Final incomplete range block
+            for (part_index, part_count) in CompleteLargestRangeBlockIterator::new(
+                index,
+                data_len,
+                max_recursion_level,
+                sub_block_sqrt,
+            ) {
+                let (top_left, bottom_right) =
+                    range_block_corners(part_index, part_count, sub_block_sqrt);
+                draw((index, count), top_left, bottom_right)?;
+            }
+        } else {
+            let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+            draw((index, count), top_left, bottom_right)?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// An 8-bit RGBA image.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// An opaque RGBA pixel.
+fn rgba(color: Rgb) -> [u8; 4] {
+    [color.r, color.g, color.b, 255]
+}
+
+impl Image {
+    fn new(width: u32, height: u32, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: rgba(background).repeat(width as usize * height as usize),
+        }
+    }
+
+    /// Fills the pixels in `[x0, x1) x [y0, y1)`.
+    fn fill_rect(&mut self, (x0, y0): (u32, u32), (x1, y1): (u32, u32), color: Rgb) {
+        let color = rgba(color);
+        for y in y0..y1.min(self.height) {
+            let row = y as usize * self.width as usize;
+            for x in x0..x1.min(self.width) {
+                let i = 4 * (row + x as usize);
+                self.pixels[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    pub fn write_png(&self, writer: impl Write) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)
+    }
+}
+
+/// Renders the range blocks of `data` at a single recursion level.
+/// `other_data` is the file that `data` is compared against in `RenderColorMode::Diff`.
+pub fn render(
+    data: &[u8],
+    other_data: Option<&[u8]>,
+    options: &RenderOptions,
+) -> Result<Image, RenderError> {
+    if data.is_empty() {
+        return Err(RenderError::EmptyData);
+    }
+    if options.color_mode == RenderColorMode::Diff && other_data.is_none() {
+        return Err(RenderError::MissingOtherData);
+    }
+
+    let sub_block_sqrt = options.sub_block_sqrt;
+    let data_len = data.len() as u64;
+    let recursion_level = options
+        .recursion_level
+        .min(max_recursion_level(data_len, sub_block_sqrt));
+
+    // The size of a range block's side in cells, at the rendered recursion level.
+    let block_cells = sub_block_sqrt.pow(recursion_level);
+    let cell_pixels = options.block_pixels as f64 / block_cells as f64;
+    let view = View::whole_file(data_len, sub_block_sqrt, cell_pixels)?;
+
+    render_view(
+        data_len,
+        &view,
+        recursion_level,
+        sub_block_sqrt,
+        |index, count| {
+            let byte_count = count.min(data_len - index);
+            match options.color_mode {
+                RenderColorMode::Value | RenderColorMode::Semantic01 => {
+                    let color_fn = if options.color_mode == RenderColorMode::Value {
+                        byte_color_rgb
+                    } else {
+                        semantic01_color_rgb
+                    };
+                    let (r, g, b) = RangeBlockColorSum::new(data, color_fn).value(index, count);
+                    Rgb::new(
+                        (r / byte_count) as u8,
+                        (g / byte_count) as u8,
+                        (b / byte_count) as u8,
+                    )
+                }
+                RenderColorMode::Diff => {
+                    let other_data = other_data.expect("checked above");
+                    let diff_bytes = RangeBlockDiff::new(data, other_data).value(index, count);
+                    diff_color(diff_bytes, count)
+                }
+            }
+        },
+    )
+}
+
+/// Renders the range blocks of `data_len` bytes at `recursion_level` (clamped to the maximum
+/// level) that `view` shows, filling each with `block_color(index, count)`.
+pub fn render_view(
+    data_len: u64,
+    view: &View,
+    recursion_level: u32,
+    sub_block_sqrt: u64,
+    block_color: impl Fn(u64, u64) -> Rgb,
+) -> Result<Image, RenderError> {
+    let (width, height) = (u64::from(view.width), u64::from(view.height));
+    if width * height > MAX_PIXELS {
+        return Err(RenderError::TooLarge { width, height });
+    }
+
+    // Gray background, as in the main view.
+    let mut image = Image::new(view.width, view.height, Rgb::GRAY);
+    let pixel = |cell: CellCoords| {
+        let (x, y) = view.pixel(cell);
+        let clamp = |v: f64, max: u32| v.round().clamp(0.0, max as f64) as u32;
+        (clamp(x, view.width), clamp(y, view.height))
+    };
+
+    // (The color of a split final range block is only calculated once.)
+    let mut previous: Option<((u64, u64), Rgb)> = None;
+    // (Never stopped early.)
+    let _ = for_each_visible_block(
+        data_len,
+        view,
+        recursion_level,
+        sub_block_sqrt,
+        |(index, count), top_left, bottom_right| {
+            let color = match previous {
+                Some((block, color)) if block == (index, count) => color,
+                _ => block_color(index, count),
+            };
+            previous = Some(((index, count), color));
+            image.fill_rect(pixel(top_left), pixel(bottom_right), color);
+            ControlFlow::Continue(())
+        },
+    );
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_view_matches_whole_file() {
+        let data: Vec<u8> = (0..5000).map(|i| (i * 7 % 251) as u8).collect();
+        let options = RenderOptions {
+            recursion_level: 1,
+            block_pixels: 8,
+            color_mode: RenderColorMode::Value,
+            sub_block_sqrt: 4,
+        };
+        let whole = render(&data, None, &options).unwrap();
+
+        // A view of a region of the same map, with the same scale.
+        let (x, y) = (40, 24);
+        let view = View {
+            origin: (x as f64 / 2.0, y as f64 / 2.0),
+            cell_pixels: 2.0,
+            width: 64,
+            height: 32,
+        };
+        let part = render_view(data.len() as u64, &view, 1, 4, |index, count| {
+            let count = count.min(data.len() as u64 - index);
+            let (r, g, b) = RangeBlockColorSum::new(&data, byte_color_rgb).value(index, count);
+            Rgb::new((r / count) as u8, (g / count) as u8, (b / count) as u8)
+        })
+        .unwrap();
+
+        for row in 0..part.height as usize {
+            let part_row = &part.pixels[4 * row * 64..][..4 * 64];
+            let whole_row = &whole.pixels[4 * ((y + row) * whole.width as usize + x)..][..4 * 64];
+            assert_eq!(part_row, whole_row);
+        }
+    }
+}
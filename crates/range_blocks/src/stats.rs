@@ -0,0 +1,259 @@
+//! Combined statistics on range block contents, computed in one pass and cached together.
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{Palette, Rgb};
+use crate::Cacheable;
+
+/// Statistics on the bytes of a range block. Statistics of sub-blocks can be merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockStats {
+    /// The number of bytes: fewer than the range block size past the end of the data.
+    pub len: u64,
+    pub sum: u64,
+    /// RGB sums of the byte colors, for each of `Palette::ALL`.
+    pub color_sums: [(u64, u64, u64); Palette::ALL.len()],
+    /// The minimum byte value (`u8::MAX` if `len` is 0).
+    pub min: u8,
+    /// The maximum byte value (0 if `len` is 0).
+    pub max: u8,
+    pub zero_count: u64,
+    pub ff_count: u64,
+    /// The number of printable ASCII bytes (0x20 to 0x7E).
+    pub printable_count: u64,
+    /// Byte counts by the high 4 bits of each byte value: a 16-bin sketch of the byte histogram.
+    pub histogram: [u64; 16],
+}
+
+impl Default for BlockStats {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl BlockStats {
+    /// Statistics of no bytes. Merging with `EMPTY` has no effect.
+    pub const EMPTY: Self = Self {
+        len: 0,
+        sum: 0,
+        color_sums: [(0, 0, 0); Palette::ALL.len()],
+        min: u8::MAX,
+        max: 0,
+        zero_count: 0,
+        ff_count: 0,
+        printable_count: 0,
+        histogram: [0; 16],
+    };
+
+    /// ```
+    /// use range_blocks::stats::BlockStats;
+    ///
+    /// let stats = BlockStats::new(b"\0\0AB");
+    /// assert_eq!((stats.len, stats.sum, stats.min, stats.max), (4, 131, 0, b'B'));
+    /// assert_eq!((stats.zero_count, stats.printable_count), (2, 2));
+    /// assert_eq!(stats.histogram[0x4], 2);
+    /// ```
+    pub fn new(data: &[u8]) -> Self {
+        // Count each byte value once, then derive everything else from the counts.
+        let mut counts = [0u64; 256];
+        for &byte in data {
+            counts[byte as usize] += 1;
+        }
+
+        let mut stats = Self::EMPTY;
+        for (byte, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let byte = byte as u8;
+
+            stats.len += count;
+            stats.sum += byte as u64 * count;
+            for (sums, palette) in stats.color_sums.iter_mut().zip(Palette::ALL) {
+                let (r, g, b) = palette.color(byte).to_u64s();
+                *sums = (sums.0 + r * count, sums.1 + g * count, sums.2 + b * count);
+            }
+            stats.min = stats.min.min(byte);
+            stats.max = stats.max.max(byte);
+            if byte == 0 {
+                stats.zero_count += count;
+            }
+            if byte == 0xFF {
+                stats.ff_count += count;
+            }
+            if (0x20..=0x7E).contains(&byte) {
+                stats.printable_count += count;
+            }
+            stats.histogram[byte as usize >> 4] += count;
+        }
+
+        stats
+    }
+
+    /// Combines the statistics of two separate byte ranges.
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut color_sums = self.color_sums;
+        for (sums, other) in color_sums.iter_mut().zip(other.color_sums) {
+            *sums = (sums.0 + other.0, sums.1 + other.1, sums.2 + other.2);
+        }
+        let mut histogram = self.histogram;
+        for (count, other) in histogram.iter_mut().zip(other.histogram) {
+            *count += other;
+        }
+
+        Self {
+            len: self.len + other.len,
+            sum: self.sum + other.sum,
+            color_sums,
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            zero_count: self.zero_count + other.zero_count,
+            ff_count: self.ff_count + other.ff_count,
+            printable_count: self.printable_count + other.printable_count,
+            histogram,
+        }
+    }
+
+    /// The fraction of bytes counted by `count`.
+    fn fraction(&self, count: u64) -> f32 {
+        if self.len == 0 {
+            0.0
+        } else {
+            count as f32 / self.len as f32
+        }
+    }
+
+    pub fn average(&self) -> f32 {
+        self.fraction(self.sum)
+    }
+
+    /// The average color of the bytes in `palette`.
+    pub fn average_color(&self, palette: Palette) -> Rgb {
+        let (r, g, b) = self.color_sums[palette as usize];
+        Rgb::new(
+            self.fraction(r) as u8,
+            self.fraction(g) as u8,
+            self.fraction(b) as u8,
+        )
+    }
+
+    pub fn zero_fraction(&self) -> f32 {
+        self.fraction(self.zero_count)
+    }
+
+    pub fn ff_fraction(&self) -> f32 {
+        self.fraction(self.ff_count)
+    }
+
+    /// `max - min`: 0 for uniform blocks.
+    pub fn range(&self) -> u8 {
+        self.max.saturating_sub(self.min)
+    }
+
+    /// The value of every byte, if they are all the same.
+    ///
+    /// ```
+    /// use range_blocks::stats::BlockStats;
+    ///
+    /// assert_eq!(BlockStats::new(&[0xFF; 64]).uniform_value(), Some(0xFF));
+    /// assert_eq!(BlockStats::new(&[0, 0, 1]).uniform_value(), None);
+    /// assert_eq!(BlockStats::new(&[]).uniform_value(), None);
+    /// ```
+    pub fn uniform_value(&self) -> Option<u8> {
+        (self.len > 0 && self.min == self.max).then_some(self.min)
+    }
+
+    pub fn printable_fraction(&self) -> f32 {
+        self.fraction(self.printable_count)
+    }
+
+    /// Shannon entropy of the histogram sketch, scaled to 0.0 - 1.0.
+    /// This is a lower bound estimate of the entropy of the byte values.
+    ///
+    /// ```
+    /// use range_blocks::stats::BlockStats;
+    ///
+    /// assert_eq!(BlockStats::new(&[7; 100]).sketch_entropy(), 0.0);
+    /// let all_bytes: Vec<u8> = (0..=255).collect();
+    /// assert_eq!(BlockStats::new(&all_bytes).sketch_entropy(), 1.0);
+    /// ```
+    pub fn sketch_entropy(&self) -> f32 {
+        let bits: f32 = self
+            .histogram
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = self.fraction(count);
+                -p * p.log2()
+            })
+            .sum();
+
+        bits / (self.histogram.len() as f32).log2()
+    }
+}
+
+/// `RangeBlockStats` is a `Cacheable` implementor that allows cached access to the
+/// `BlockStats` of a range block.
+pub struct RangeBlockStats<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> RangeBlockStats<'a> {
+    /// Identifies caches of `BlockStats` in saved cache files:
+    /// change this whenever `BlockStats` changes.
+    pub const CACHE_ID: &'static str = "stats/2";
+
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl Cacheable<BlockStats> for RangeBlockStats<'_> {
+    fn value(&self, index: u64, count: u64) -> BlockStats {
+        let limit =
+            usize::try_from((self.data.len() as u64).min(index + count)).unwrap_or(usize::MAX);
+        let index = usize::try_from(index).unwrap_or(usize::MAX);
+
+        self.data
+            .get(index..limit)
+            .map(BlockStats::new)
+            .unwrap_or_default()
+    }
+
+    fn value_from_sub_blocks(&self, value: &[BlockStats]) -> BlockStats {
+        value
+            .iter()
+            .fold(BlockStats::EMPTY, |stats, sub_block| stats.merge(sub_block))
+    }
+
+    /// `BlockStats` are large: calculate small range blocks on demand instead.
+    fn min_cached_recursion_level(&self) -> u32 {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RangeBlockCache;
+    use rand::Rng;
+
+    #[test]
+    fn merged_stats() {
+        let mut rng = rand::thread_rng();
+        let data: Vec<u8> = (0..100_000).map(|_| rng.gen_range(0..=0x90)).collect();
+
+        let stats = RangeBlockStats::new(&data);
+        let cache = RangeBlockCache::generate(&stats, data.len(), 4);
+
+        for (index, count) in [
+            (0, 4096),
+            (4096, 4096),
+            (0, 65536),
+            (65536, 65536),
+            (0, 1 << 20),
+        ] {
+            assert_eq!(cache.get(index, count), Some(stats.value(index, count)));
+        }
+        // Calculated on demand
+        assert_eq!(cache.get(0, 256), None);
+        assert_eq!(cache.get(0, 1 << 20).unwrap(), BlockStats::new(&data));
+    }
+}
@@ -0,0 +1,291 @@
+//! Extraction of readable strings, in the manner of the `strings` tool.
+//!
+//! ```
+//! use range_blocks::strings::{find_strings, StringEncoding};
+//!
+//! let data = b"\x00hello\x00\x00H\x00i\x00!\x00\x00\x00";
+//! let strings = find_strings(data, &StringEncoding::ALL, 3, 100);
+//!
+//! assert_eq!(strings.len(), 2);
+//! assert_eq!((strings[0].index, strings[0].len), (1, 5));
+//! assert_eq!(strings[0].text, "hello");
+//! assert_eq!(strings[1].encoding, StringEncoding::Utf16Le);
+//! assert_eq!(strings[1].text, "Hi!");
+//! ```
+
+/// A character encoding that strings are searched for in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StringEncoding {
+    /// Printable ASCII characters, spaces, and tabs.
+    Ascii,
+    /// Printable UTF-8 characters. Only strings with at least one non-ASCII character are
+    /// found: the rest are found by `Ascii`.
+    Utf8,
+    /// Printable UTF-16LE characters below U+0800, at even offsets.
+    /// (Almost any pair of bytes is a valid UTF-16 code unit: without these limits,
+    /// most random data would be found as text.)
+    Utf16Le,
+    /// Same as `Utf16Le`, but big-endian.
+    Utf16Be,
+}
+
+impl StringEncoding {
+    pub const ALL: [Self; 4] = [Self::Ascii, Self::Utf8, Self::Utf16Le, Self::Utf16Be];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ascii => "ASCII",
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+        }
+    }
+
+    /// Decodes the printable character at `index`, and its length in bytes.
+    fn decode(self, data: &[u8], index: usize) -> Option<(char, usize)> {
+        match self {
+            Self::Ascii => {
+                let c = *data.get(index)? as char;
+                (c.is_ascii() && is_printable(c)).then_some((c, 1))
+            }
+            Self::Utf8 => {
+                let len = match *data.get(index)? {
+                    0x00..=0x7F => 1,
+                    0xC0..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF7 => 4,
+                    _ => return None,
+                };
+                let c = std::str::from_utf8(data.get(index..index + len)?)
+                    .ok()?
+                    .chars()
+                    .next()?;
+                is_printable(c).then_some((c, len))
+            }
+            Self::Utf16Le | Self::Utf16Be => {
+                let bytes = [*data.get(index)?, *data.get(index + 1)?];
+                let unit = if self == Self::Utf16Le {
+                    u16::from_le_bytes(bytes)
+                } else {
+                    u16::from_be_bytes(bytes)
+                };
+                let c = char::from_u32(unit.into()).filter(|&c| c < '\u{800}')?;
+                is_printable(c).then_some((c, 2))
+            }
+        }
+    }
+
+    /// The number of bytes skipped after a byte sequence that isn't a printable character.
+    fn step(self) -> usize {
+        match self {
+            Self::Ascii | Self::Utf8 => 1,
+            Self::Utf16Le | Self::Utf16Be => 2,
+        }
+    }
+}
+
+fn is_printable(c: char) -> bool {
+    c == ' ' || c == '\t' || !(c.is_control() || c.is_whitespace())
+}
+
+/// A string found in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundString {
+    pub index: u64,
+    /// Length in bytes.
+    pub len: u64,
+    pub encoding: StringEncoding,
+    pub text: String,
+}
+
+/// Finds strings of at least `min_len` characters in any of `encodings`, ordered by index.
+/// Stops after the first `limit` strings.
+pub fn find_strings(
+    data: &[u8],
+    encodings: &[StringEncoding],
+    min_len: usize,
+    limit: usize,
+) -> Vec<FoundString> {
+    let mut strings: Vec<FoundString> = encodings
+        .iter()
+        .flat_map(|&encoding| find_encoded_strings(data, encoding, min_len.max(1), limit))
+        .collect();
+
+    strings.sort_by_key(|string| (string.index, string.encoding));
+    strings.truncate(limit);
+    strings
+}
+
+fn find_encoded_strings(
+    data: &[u8],
+    encoding: StringEncoding,
+    min_len: usize,
+    limit: usize,
+) -> Vec<FoundString> {
+    let mut strings = Vec::new();
+    let mut index = 0;
+
+    while index < data.len() && strings.len() < limit {
+        let start = index;
+        let mut text = String::new();
+        let (mut char_count, mut non_ascii) = (0, false);
+
+        while let Some((c, len)) = encoding.decode(data, index) {
+            text.push(c);
+            char_count += 1;
+            non_ascii |= !c.is_ascii();
+            index += len;
+        }
+
+        if index == start {
+            index += encoding.step();
+        } else if char_count >= min_len && (encoding != StringEncoding::Utf8 || non_ascii) {
+            strings.push(FoundString {
+                index: start as u64,
+                len: (index - start) as u64,
+                encoding,
+                text,
+            });
+        }
+    }
+
+    strings
+}
+
+/// The byte ranges covered by a set of strings, for quickly finding how much of a
+/// range block they cover.
+#[derive(Debug, Default)]
+pub struct StringCoverage {
+    /// Sorted, non-overlapping (start, end) ranges.
+    ranges: Vec<(u64, u64)>,
+    /// The total length of the ranges before each range, and of all ranges.
+    preceding_len: Vec<u64>,
+}
+
+impl StringCoverage {
+    pub fn new<'a>(strings: impl IntoIterator<Item = &'a FoundString>) -> Self {
+        let mut sorted: Vec<(u64, u64)> = strings
+            .into_iter()
+            .map(|string| (string.index, string.index + string.len))
+            .collect();
+        sorted.sort_unstable();
+
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for (start, end) in sorted {
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+
+        let mut preceding_len = vec![0];
+        for &(start, end) in &ranges {
+            preceding_len.push(preceding_len[preceding_len.len() - 1] + end - start);
+        }
+
+        Self {
+            ranges,
+            preceding_len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of bytes in the range block that are part of a string.
+    ///
+    /// ```
+    /// use range_blocks::strings::{find_strings, StringCoverage, StringEncoding};
+    ///
+    /// let strings = find_strings(b"\0\0abcd\0\0efgh\0", &[StringEncoding::Ascii], 4, 10);
+    /// let coverage = StringCoverage::new(&strings);
+    /// assert_eq!(coverage.covered_len(0, 16), 8);
+    /// assert_eq!(coverage.covered_len(4, 4), 2);
+    /// ```
+    pub fn covered_len(&self, index: u64, count: u64) -> u64 {
+        self.covered_before(index.saturating_add(count)) - self.covered_before(index)
+    }
+
+    /// The number of bytes before `index` that are part of a string.
+    fn covered_before(&self, index: u64) -> u64 {
+        let i = self.ranges.partition_point(|&(_, end)| end <= index);
+        let partial = match self.ranges.get(i) {
+            Some(&(start, _)) if start < index => index - start,
+            _ => 0,
+        };
+        self.preceding_len.get(i).copied().unwrap_or(0) + partial
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(data: &[u8], encoding: StringEncoding, min_len: usize) -> Vec<(u64, String)> {
+        find_strings(data, &[encoding], min_len, usize::MAX)
+            .into_iter()
+            .map(|string| (string.index, string.text))
+            .collect()
+    }
+
+    #[test]
+    fn encodings() {
+        let data = b"ab\tc\nxyz\x80 caf\xC3\xA9 \xFF";
+        assert_eq!(
+            texts(data, StringEncoding::Ascii, 3),
+            vec![
+                (0, "ab\tc".to_string()),
+                (5, "xyz".to_string()),
+                (9, " caf".to_string())
+            ]
+        );
+        assert_eq!(texts(data, StringEncoding::Ascii, 5), vec![]);
+        assert_eq!(
+            texts(data, StringEncoding::Utf8, 3),
+            vec![(9, " café ".to_string())]
+        );
+
+        let utf16: Vec<u8> = "\0Ωmega!"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            texts(&utf16, StringEncoding::Utf16Le, 4),
+            vec![(2, "Ωmega!".to_string())]
+        );
+        // UTF-16 strings are only found at even offsets.
+        assert_eq!(texts(&utf16[1..], StringEncoding::Utf16Le, 4), vec![]);
+        assert_eq!(texts(&utf16, StringEncoding::Utf16Be, 4), vec![]);
+
+        let utf16: Vec<u8> = "Ωmega!".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(
+            texts(&utf16, StringEncoding::Utf16Be, 4),
+            vec![(0, "Ωmega!".to_string())]
+        );
+
+        assert_eq!(
+            find_strings(b"abcd\0efgh\0ijkl", &StringEncoding::ALL, 4, 2).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        let string = |index, len| FoundString {
+            index,
+            len,
+            encoding: StringEncoding::Ascii,
+            text: String::new(),
+        };
+        let strings = [string(10, 10), string(15, 10), string(40, 5)];
+        let coverage = StringCoverage::new(&strings);
+
+        assert_eq!(coverage.covered_len(0, 10), 0);
+        assert_eq!(coverage.covered_len(0, 100), 20);
+        assert_eq!(coverage.covered_len(12, 30), 15);
+        assert_eq!(coverage.covered_len(44, 1), 1);
+        assert_eq!(coverage.covered_len(45, 100), 0);
+        assert_eq!(StringCoverage::default().covered_len(0, 100), 0);
+    }
+}
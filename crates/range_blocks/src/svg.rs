@@ -0,0 +1,194 @@
+//! Rendering of range block maps to SVG vector images.
+//!
+//! Range blocks are drawn as rectangles in the same layout as `render`, optionally with
+//! `RangeBorder` outlines (e.g. of a selection) and address labels.
+
+use std::fmt::Write;
+use std::ops::ControlFlow;
+
+use crate::color::{text_color, Rgb};
+use crate::range_block_corners;
+use crate::range_border::{LoopsIter, RangeBorder};
+use crate::render::{for_each_visible_block, RenderError, View};
+
+/// Limit on the number of range blocks drawn, to keep images usable in other programs.
+const MAX_BLOCKS: usize = 1 << 20;
+
+/// Address labels smaller than this (in pixels) aren't drawn.
+const MIN_LABEL_SIZE: f64 = 4.0;
+
+/// The outer border of a set of range blocks.
+pub struct Outline {
+    /// `(index, count)` of each range block.
+    pub range_blocks: Vec<(u64, u64)>,
+    pub color: Rgb,
+    /// Stroke width, in pixels.
+    pub width: f64,
+}
+
+#[derive(Default)]
+pub struct SvgOptions {
+    pub outlines: Vec<Outline>,
+    /// Labels each (complete) range block with its starting address.
+    pub address_labels: bool,
+}
+
+/// `#RRGGBB`
+fn hex_color(color: Rgb) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+}
+
+/// Renders the range blocks of `data_len` bytes at `recursion_level` (clamped to the maximum
+/// level) that `view` shows, filling each with `block_color(index, count)`.
+///
+/// ```
+/// use range_blocks::{color::Rgb, render::View, svg::{render_svg, SvgOptions}};
+///
+/// let view = View::whole_file(256, 4, 1.0).unwrap();
+/// let svg = render_svg(256, &view, 0, 4, |_, _| Rgb::BLACK, &SvgOptions::default()).unwrap();
+/// assert_eq!(svg.matches("<rect").count(), 1 + 256);
+/// ```
+pub fn render_svg(
+    data_len: u64,
+    view: &View,
+    recursion_level: u32,
+    sub_block_sqrt: u64,
+    block_color: impl Fn(u64, u64) -> Rgb,
+    options: &SvgOptions,
+) -> Result<String, RenderError> {
+    let mut blocks = Vec::new();
+    let visited = for_each_visible_block(
+        data_len,
+        view,
+        recursion_level,
+        sub_block_sqrt,
+        |block, top_left, bottom_right| {
+            if blocks.len() >= MAX_BLOCKS {
+                return ControlFlow::Break(());
+            }
+            blocks.push((block, view.pixel(top_left), view.pixel(bottom_right)));
+            ControlFlow::Continue(())
+        },
+    );
+    if visited.is_break() {
+        return Err(RenderError::TooManyBlocks);
+    }
+
+    let (width, height) = (view.width, view.height);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n"
+    );
+    // Gray background, as in the main view.
+    let _ = writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        hex_color(Rgb::GRAY)
+    );
+
+    let mut labels = String::new();
+    let mut previous: Option<((u64, u64), Rgb)> = None;
+    svg.push_str("<g shape-rendering=\"crispEdges\">\n");
+    for ((index, count), (left, top), (right, bottom)) in blocks {
+        let color = match previous {
+            Some((block, color)) if block == (index, count) => color,
+            _ => block_color(index, count),
+        };
+        previous = Some(((index, count), color));
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{left:.2}\" y=\"{top:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>",
+            right - left,
+            bottom - top,
+            hex_color(color)
+        );
+
+        // (Incomplete final range blocks aren't labeled.)
+        let font_size = (right - left) / 7.0;
+        if options.address_labels && index + count <= data_len && font_size >= MIN_LABEL_SIZE {
+            let _ = writeln!(
+                labels,
+                "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{font_size:.2}\" fill=\"{}\">0x{index:08X}</text>",
+                (left + right) / 2.0,
+                (top + bottom) / 2.0,
+                hex_color(text_color(color))
+            );
+        }
+    }
+    svg.push_str("</g>\n");
+
+    for outline in &options.outlines {
+        let mut range_border = RangeBorder::default();
+        for &(index, count) in &outline.range_blocks {
+            let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+            range_border.add_rect(top_left, bottom_right);
+        }
+
+        let mut path = String::new();
+        let mut loops_iter = LoopsIter::new(range_border.edges);
+        while let Some(loop_iter) = loops_iter.next() {
+            for (i, edge) in loop_iter.enumerate() {
+                let (x, y) = view.pixel(edge.start);
+                let command = if i == 0 { 'M' } else { 'L' };
+                let _ = write!(path, "{command}{x:.2} {y:.2} ");
+            }
+            path.push('Z');
+        }
+        let _ = writeln!(
+            svg,
+            "<path d=\"{path}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" \
+             stroke-linejoin=\"round\"/>",
+            hex_color(outline.color),
+            outline.width
+        );
+    }
+
+    if !labels.is_empty() {
+        svg.push_str(
+            "<g font-family=\"monospace\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        );
+        svg.push_str(&labels);
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlines_and_labels() {
+        let view = View::whole_file(4096, 4, 2.0).unwrap();
+        let options = SvgOptions {
+            outlines: vec![Outline {
+                // Two adjacent range blocks, outlined as one rectangle.
+                range_blocks: vec![(0, 256), (256, 256)],
+                color: Rgb::new(0, 255, 255),
+                width: 2.0,
+            }],
+            address_labels: true,
+        };
+        let svg = render_svg(4096, &view, 2, 4, |_, _| Rgb::BLACK, &options).unwrap();
+
+        assert_eq!(svg.matches("<rect").count(), 1 + 16);
+        assert!(svg.contains("d=\"M0.00 0.00 L64.00 0.00 L64.00 32.00 L0.00 32.00 Z\""));
+        // Light text on the black range blocks.
+        assert!(svg.contains("fill=\"#FFFFFF\">0x00000100</text>"));
+    }
+
+    #[test]
+    fn partial_view() {
+        let view = View {
+            origin: (16.0, 0.0),
+            cell_pixels: 1.0,
+            width: 16,
+            height: 16,
+        };
+        let svg = render_svg(4096, &view, 2, 4, |_, _| Rgb::BLACK, &SvgOptions::default());
+        // Only the range block at the right of the top row is visible.
+        assert_eq!(svg.unwrap().matches("<rect").count(), 1 + 1);
+    }
+}
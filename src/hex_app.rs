@@ -1,31 +1,39 @@
-use crate::{
-    range_blocks::{Cacheable, RangeBlockCache, RangeBlockColorSum, RangeBlockDiff, RangeBlockSum},
-    utilities::{byte_color_rgb, semantic01_color_rgb},
-};
-use egui::{Vec2, Window};
+use crate::patch::PatchFormat;
+use checksum_window::Checksums;
+use command_palette::CommandPalette;
+use commands::{Command, Keybindings};
+use egui::{ComboBox, Ui, Vec2, Window};
+use export_window::Export;
+use loaded_file::{LoadedFile, SavedCacheSettings};
+use palette_window::Palettes;
 use rand::Rng;
+use range_blocks::{
+    color::{ColorScheme, Palette, Rgb},
+    diff::{DiffRun, DiffSearch},
+    get_cell_offset, Cacheable, RangeBlockCache, RangeBlockDiff, RangeBlockVariability,
+};
 use std::cell::RefCell;
+use strings_window::Strings;
+mod checksum_window;
+mod command_palette;
+mod commands;
+mod diff_list;
+mod export_window;
 mod frame_history;
 mod hex_view;
 mod info_bar;
+mod keybindings_window;
+mod legend;
+mod loaded_file;
 mod main_view;
+mod minimap;
+mod navigation;
+mod palette_window;
+mod patch_window;
+mod statistics_window;
+mod strings_window;
 mod top_bar;
 
-#[derive(Debug, PartialEq)]
-enum WhichFile {
-    File0,
-    File1,
-}
-
-impl WhichFile {
-    pub fn next(&self) -> Self {
-        match self {
-            WhichFile::File0 => WhichFile::File1,
-            WhichFile::File1 => WhichFile::File0,
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum CellViewMode {
     Hex,
@@ -58,6 +66,19 @@ enum ColorMode {
     Value,
     Diff,
     Semantic01,
+    Variability,
+    /// Fraction of printable ASCII bytes
+    Printable,
+    /// Estimated entropy of byte values
+    Entropy,
+    /// Range (max - min) of byte values
+    Range,
+    /// Fraction of 0x00 and 0xFF bytes
+    Padding,
+    /// Highlights blocks of a single byte value
+    Uniform,
+    /// The selected user-defined palette
+    Custom,
 }
 
 impl ColorMode {
@@ -65,16 +86,78 @@ impl ColorMode {
         match self {
             ColorMode::Value => ColorMode::Diff,
             ColorMode::Diff => ColorMode::Semantic01,
-            ColorMode::Semantic01 => ColorMode::Value,
+            ColorMode::Semantic01 => ColorMode::Variability,
+            ColorMode::Variability => ColorMode::Printable,
+            ColorMode::Printable => ColorMode::Entropy,
+            ColorMode::Entropy => ColorMode::Range,
+            ColorMode::Range => ColorMode::Padding,
+            ColorMode::Padding => ColorMode::Uniform,
+            ColorMode::Uniform => ColorMode::Custom,
+            ColorMode::Custom => ColorMode::Value,
         }
     }
 }
 
+/// A byte range that a window describes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum TargetRange {
+    /// The range block containing the selected index, at the current visible recursion level.
+    SelectedBlock,
+    /// The bytes shown in the hex view.
+    Selection,
+    File,
+}
+
+impl TargetRange {
+    const ALL: [Self; 3] = [Self::SelectedBlock, Self::Selection, Self::File];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::SelectedBlock => "Selected block",
+            Self::Selection => "Selection",
+            Self::File => "Whole file",
+        }
+    }
+
+    fn combo_box(&mut self, ui: &mut Ui) {
+        ComboBox::from_label("Range")
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for range in Self::ALL {
+                    ui.selectable_value(self, range, range.name());
+                }
+            });
+    }
+}
+
+/// How `main_view` arranges its range block views.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum MainViewLayout {
+    /// The active file only.
+    Single,
+    /// The active file on the left, the reference file on the right.
+    SideBySide,
+    /// The active file on top, the reference file below.
+    Stacked,
+}
+
 fn random_pattern(len: usize) -> Vec<u8> {
     let mut rng = rand::thread_rng();
     (0..len).map(|_| rng.gen_range(0..=255)).collect()
 }
 
+/// Generates a `RangeBlockCache`, multi-threaded on native.
+fn generate_cache<T: Clone + Send + Sync>(
+    cacheable: &(impl Cacheable<T> + Sync),
+    data_len: usize,
+) -> RangeBlockCache<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return RangeBlockCache::generate_parallel(cacheable, data_len, HexApp::SUB_BLOCK_SQRT);
+
+    #[cfg(target_arch = "wasm32")]
+    return RangeBlockCache::generate(cacheable, data_len, HexApp::SUB_BLOCK_SQRT);
+}
+
 pub struct UIConfig {
     pub final_incomplete_block: bool,
     pub cell_text: bool,
@@ -89,107 +172,184 @@ pub struct UIConfig {
 }
 
 pub struct HexApp {
-    source_name0: Option<String>,
-    source_name1: Option<String>,
-    pattern0: Option<Vec<u8>>,
-    pattern1: Option<Vec<u8>>,
-    cache0: RangeBlockCache<u64>,
-    cache1: RangeBlockCache<u64>,
+    files: Vec<LoadedFile>,
+    /// Diff between the active and reference files.
     diff_cache: RangeBlockCache<Option<usize>>,
-    color_cache_value0: RangeBlockCache<(u64, u64, u64)>,
-    color_cache_value1: RangeBlockCache<(u64, u64, u64)>,
-    color_cache_semantic01_0: RangeBlockCache<(u64, u64, u64)>,
-    color_cache_semantic01_1: RangeBlockCache<(u64, u64, u64)>,
+    /// The (active, reference) file indices that `diff_cache` was generated for.
+    diff_cache_files: Option<(usize, usize)>,
+    /// Contiguous differing runs between the active and reference files.
+    diff_runs: Vec<DiffRun>,
+    /// Variability of all files relative to the reference file.
+    variability_cache: RangeBlockCache<u64>,
+    /// The (reference file index, file count) that `variability_cache` was generated for.
+    variability_cache_files: Option<(usize, usize)>,
     zoom: f32,
     pan: Vec2,
-    active_file: WhichFile,
+    active_file: usize,
+    reference_file: usize,
     dbg_notes: String,
     dbg_flag: bool,
     pan_velocity: Vec2,
+    /// The animated zoom and pan transition in progress, if any.
+    view_transition: Option<navigation::ViewTransition>,
     last_update_time: f64,
     hover_address: Option<usize>,
     cell_view_mode: CellViewMode,
     color_mode: ColorMode,
     color_averaging: bool,
+    main_view_layout: MainViewLayout,
+    split_diff_overlay: bool,
     hex_view_color_mode: bool,
+    hex_view_dual: bool,
     hex_view_columns: u8,
     hex_view_rows: u8,
     selected_index: Option<usize>,
     selected_range_block: Option<(u64, u64)>,
     rect_draw_count: RefCell<usize>,
+    /// Cached range block fills of each `main_view` view, at the rendered recursion level
+    /// and the next coarser level.
+    block_meshes: [[Option<main_view::BlockMesh>; 2]; 2],
+    /// The size of (each view of) the main view, as of the last frame.
+    main_view_size: Vec2,
+    /// `true` if arrow keys navigate range blocks in the main view (since it was last clicked),
+    /// rather than bytes in the hex view.
+    main_view_focus: bool,
+    minimap: bool,
+    minimap_mesh: Option<main_view::BlockMesh>,
     ui_config_window: bool,
+    diff_list_window: bool,
+    patch_window: bool,
+    statistics_window: bool,
+    statistics_range: TargetRange,
+    strings_window: bool,
+    strings: Strings,
+    checksum_window: bool,
+    checksums: Checksums,
+    keybindings: Keybindings,
+    keybindings_window: bool,
+    /// The command whose new shortcut is being captured in the keybindings window, if any.
+    keybinding_capture: Option<Command>,
+    command_palette: CommandPalette,
+    palettes: Palettes,
+    palette_window: bool,
+    color_scheme: ColorScheme,
+    /// The color of each byte value in the current color mode and scheme, as of this frame
+    /// (`ColorScheme::byte_color` for color modes that don't color bytes by value).
+    byte_color_table: [Rgb; 256],
+    /// What `byte_color_table` was built for: the color mode, scheme, and palette revision.
+    byte_color_table_key: Option<(ColorMode, ColorScheme, u64)>,
+    /// Incremented whenever `byte_color_table` is rebuilt.
+    byte_color_table_revision: u64,
+    /// Incremented whenever `byte_color_table` or the files' palette color sum caches change
+    /// (so range block colors may have changed).
+    byte_colors_revision: u64,
+    legend: bool,
+    export_window: bool,
+    export: Export,
+    patch_format: PatchFormat,
+    patch_path: String,
+    patch_status: String,
     ui_config: UIConfig,
+    cache_settings: SavedCacheSettings,
     frame_history: frame_history::FrameHistory,
 }
 
 impl HexApp {
+    /// The app name given to eframe, which also names its storage directory.
+    pub const APP_NAME: &'static str = "eframe template";
+    /// The key that the color scheme is saved under in eframe's storage.
+    const COLOR_SCHEME_STORAGE_KEY: &'static str = "color_scheme";
     const MIN_ZOOM: f32 = 0.0025;
     const MAX_ZOOM: f32 = 128.0;
     const FRICTION: f32 = 0.9;
-    pub const SUB_BLOCK_SQRT: u64 = 4;
+    pub const SUB_BLOCK_SQRT: u64 = range_blocks::DEFAULT_SUB_BLOCK_SQRT;
+    /// Limit on the number of differing runs listed in the differences window.
+    const MAX_DIFF_RUNS: usize = 10_000;
 
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let len0 = 10_000_usize;
         let mut data0 = random_pattern(len0);
         data0.extend(0..=u8::MAX);
         let len1 = 12_000_usize;
         let mut data1 = random_pattern(len1);
         data1.extend(0..=u8::MAX);
+        let cache_settings: SavedCacheSettings = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, SavedCacheSettings::STORAGE_KEY))
+            .unwrap_or_default();
 
         Self {
-            source_name0: None,
-            source_name1: None,
-            cache0: RangeBlockCache::generate(
-                &RangeBlockSum::new(&data0),
-                data0.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
-            cache1: RangeBlockCache::generate(
-                &RangeBlockSum::new(&data1),
-                data1.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
+            files: vec![
+                LoadedFile::new(None, data0, &cache_settings),
+                LoadedFile::new(None, data1, &cache_settings),
+            ],
             diff_cache: RangeBlockCache::new(),
-            color_cache_value0: RangeBlockCache::generate(
-                &RangeBlockColorSum::new(&data0, byte_color_rgb),
-                data0.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
-            color_cache_value1: RangeBlockCache::generate(
-                &RangeBlockColorSum::new(&data1, byte_color_rgb),
-                data1.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
-            color_cache_semantic01_0: RangeBlockCache::generate(
-                &RangeBlockColorSum::new(&data0, semantic01_color_rgb),
-                data0.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
-            color_cache_semantic01_1: RangeBlockCache::generate(
-                &RangeBlockColorSum::new(&data1, semantic01_color_rgb),
-                data1.len(),
-                Self::SUB_BLOCK_SQRT,
-            ),
-            pattern0: Some(data0),
-            pattern1: Some(data1),
+            diff_cache_files: None,
+            diff_runs: Vec::new(),
+            variability_cache: RangeBlockCache::new(),
+            variability_cache_files: None,
             zoom: 1.0,
             pan: Vec2::ZERO,
-            active_file: WhichFile::File0,
+            active_file: 0,
+            reference_file: 1,
             dbg_notes: String::new(),
             dbg_flag: false,
             pan_velocity: Vec2::ZERO,
+            view_transition: None,
             last_update_time: 0.0,
             hover_address: None,
             cell_view_mode: CellViewMode::Hex,
             color_mode: ColorMode::Value,
             color_averaging: true,
+            main_view_layout: MainViewLayout::Single,
+            split_diff_overlay: true,
             hex_view_color_mode: true,
+            hex_view_dual: false,
             hex_view_columns: 16,
             hex_view_rows: 32,
             selected_index: None,
             selected_range_block: None,
             rect_draw_count: RefCell::new(0),
+            block_meshes: Default::default(),
+            main_view_size: Vec2::ZERO,
+            main_view_focus: true,
+            minimap: true,
+            minimap_mesh: None,
             ui_config_window: false,
+            diff_list_window: false,
+            patch_window: false,
+            statistics_window: false,
+            statistics_range: TargetRange::SelectedBlock,
+            strings_window: false,
+            strings: Strings::default(),
+            checksum_window: false,
+            checksums: Checksums::default(),
+            keybindings: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Keybindings::STORAGE_KEY))
+                .unwrap_or_default(),
+            keybindings_window: false,
+            keybinding_capture: None,
+            command_palette: CommandPalette::default(),
+            palettes: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Palettes::STORAGE_KEY))
+                .unwrap_or_default(),
+            palette_window: false,
+            color_scheme: cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, Self::COLOR_SCHEME_STORAGE_KEY))
+                .unwrap_or(ColorScheme::Standard),
+            byte_color_table: [Rgb::BLACK; 256],
+            byte_color_table_key: None,
+            byte_color_table_revision: 0,
+            byte_colors_revision: 0,
+            legend: true,
+            export_window: false,
+            export: Export::default(),
+            patch_format: PatchFormat::Ips,
+            patch_path: String::new(),
+            patch_status: String::new(),
             ui_config: UIConfig {
                 final_incomplete_block: true,
                 cell_text: true,
@@ -202,84 +362,255 @@ impl HexApp {
                 selected_block: true,
                 cursor: true,
             },
+            cache_settings,
             frame_history: frame_history::FrameHistory::default(),
         }
     }
+
+    /// The file currently shown in the main view and hex view.
+    fn active(&self) -> Option<&LoadedFile> {
+        self.files.get(self.active_file)
+    }
+
+    /// The file that the active file is compared against.
+    fn reference(&self) -> Option<&LoadedFile> {
+        self.files.get(self.reference_file)
+    }
+
+    /// Cycles the active file through all loaded files.
+    fn next_active_file(&mut self) {
+        if !self.files.is_empty() {
+            self.active_file = (self.active_file + 1) % self.files.len();
+        }
+    }
+
+    /// Adds a loaded file and makes it the active file.
+    fn add_file(&mut self, file: LoadedFile) {
+        if self.files.len() >= RangeBlockVariability::MAX_FILES {
+            log::warn!(
+                "Can't load more than {} files",
+                RangeBlockVariability::MAX_FILES
+            );
+            return;
+        }
+        self.files.push(file);
+        self.active_file = self.files.len() - 1;
+        self.block_meshes = Default::default();
+        self.minimap_mesh = None;
+    }
+
+    /// Unloads the active file.
+    fn close_active_file(&mut self) {
+        if self.files.len() <= 1 {
+            return;
+        }
+        self.files.remove(self.active_file);
+        if self.reference_file > self.active_file {
+            self.reference_file -= 1;
+        }
+        let last = self.files.len() - 1;
+        self.active_file = self.active_file.min(last);
+        self.reference_file = self.reference_file.min(last);
+
+        // File indices have shifted: force regeneration.
+        self.diff_cache_files = None;
+        self.variability_cache_files = None;
+        self.strings.invalidate();
+        self.block_meshes = Default::default();
+        self.minimap_mesh = None;
+        self.checksums.invalidate();
+    }
+
+    /// Regenerates the file comparison caches if the files they depend on have changed.
+    fn update_comparison_caches(&mut self) {
+        let diff_cache_files = Some((self.active_file, self.reference_file));
+        if self.diff_cache_files != diff_cache_files {
+            if let (Some(active), Some(reference)) = (self.active(), self.reference()) {
+                self.diff_cache = generate_cache(
+                    &RangeBlockDiff::new(&active.data, &reference.data),
+                    std::cmp::max(active.data.len(), reference.data.len()),
+                );
+                self.diff_runs = self
+                    .diff_search()
+                    .map(|diff_search| diff_search.runs(Self::MAX_DIFF_RUNS))
+                    .unwrap_or_default();
+            }
+            self.diff_cache_files = diff_cache_files;
+        }
+
+        let variability_cache_files = Some((self.reference_file, self.files.len()));
+        if self.variability_cache_files != variability_cache_files {
+            if self.reference().is_some() {
+                self.variability_cache = generate_cache(&self.variability(), self.max_file_len());
+            }
+            self.variability_cache_files = variability_cache_files;
+        }
+    }
+
+    /// The `BlockStats` color sums palette of the current color mode and scheme, if there is one.
+    fn stats_palette(&self) -> Option<Palette> {
+        match (self.color_mode, self.color_scheme) {
+            (ColorMode::Value, ColorScheme::Standard) => Some(Palette::Value),
+            (ColorMode::Semantic01, ColorScheme::Standard) => Some(Palette::Semantic01),
+            _ => None,
+        }
+    }
+
+    /// Updates `byte_color_table` if the color mode, scheme, or palette has changed, and the
+    /// files' palette color sum caches if the current color mode colors bytes by value without
+    /// a `stats_palette`. (The caches are only regenerated once palette edits have settled:
+    /// until then, range blocks are drawn from the previous caches.)
+    fn update_byte_color_caches(&mut self, ctx: &egui::Context) {
+        let scheme = self.color_scheme;
+        let palette_revision = match self.color_mode {
+            ColorMode::Custom => self.palettes.revision(),
+            _ => 0,
+        };
+        let key = Some((self.color_mode, scheme, palette_revision));
+        if self.byte_color_table_key != key {
+            self.byte_color_table = match self.color_mode {
+                ColorMode::Semantic01 => {
+                    std::array::from_fn(|byte| scheme.semantic01_color(byte as u8))
+                }
+                ColorMode::Custom => self.palettes.color_table(),
+                _ => std::array::from_fn(|byte| scheme.byte_color(byte as u8)),
+            };
+            self.byte_color_table_key = key;
+            self.byte_color_table_revision += 1;
+            self.byte_colors_revision += 1;
+        }
+
+        let by_value = matches!(
+            self.color_mode,
+            ColorMode::Value | ColorMode::Semantic01 | ColorMode::Custom
+        );
+        if !by_value || self.stats_palette().is_some() {
+            return;
+        }
+        let time = ctx.input(|i| i.time);
+        if self.color_mode == ColorMode::Custom && !self.palettes.settled(time) {
+            ctx.request_repaint_after_secs(Palettes::SETTLE_TIME as f32);
+            return;
+        }
+        for file in &mut self.files {
+            let regenerated =
+                file.update_palette_cache(&self.byte_color_table, self.byte_color_table_revision);
+            if regenerated {
+                self.byte_colors_revision += 1;
+            }
+        }
+    }
+
+    /// `DiffSearch` over the differences between the active and reference files.
+    fn diff_search(&self) -> Option<DiffSearch<'_>> {
+        let (active, reference) = (self.active()?, self.reference()?);
+        Some(DiffSearch::new(
+            &active.data,
+            &reference.data,
+            &self.diff_cache,
+            Self::SUB_BLOCK_SQRT,
+        ))
+    }
+
+    /// Selects the next difference after the selected index.
+    fn next_difference(&mut self) {
+        let start_index = self.selected_index.map_or(0, |index| index as u64 + 1);
+        if let Some(index) = self
+            .diff_search()
+            .and_then(|diff_search| diff_search.next_difference(start_index))
+        {
+            self.go_to_index(index as usize);
+        }
+    }
+
+    /// Selects the last difference before the selected index.
+    fn previous_difference(&mut self) {
+        let limit_index = self.selected_index.map_or(u64::MAX, |index| index as u64);
+        if let Some(index) = self
+            .diff_search()
+            .and_then(|diff_search| diff_search.previous_difference(limit_index))
+        {
+            self.go_to_index(index as usize);
+        }
+    }
+
+    /// Selects `index` and centers the main view on its cell.
+    fn go_to_index(&mut self, index: usize) {
+        self.selected_index = Some(index);
+
+        let cell = get_cell_offset(index as u64, Self::SUB_BLOCK_SQRT);
+        let cell_center = Vec2::new(cell.x as f32 + 0.5, cell.y as f32 + 0.5);
+        self.pan = -cell_center * self.zoom;
+        self.pan_velocity = Vec2::ZERO;
+        self.view_transition = None;
+    }
+
+    /// `RangeBlockVariability` of all loaded files relative to the reference file.
+    fn variability(&self) -> RangeBlockVariability<'_> {
+        RangeBlockVariability::new(
+            self.files.iter().map(|file| file.data.as_slice()).collect(),
+            self.reference_file,
+        )
+    }
+
+    /// The (index, len) of a `TargetRange`, if there is one.
+    fn target_range(&self, range: TargetRange) -> Option<(u64, u64)> {
+        match range {
+            TargetRange::SelectedBlock => self.selected_range_block,
+            TargetRange::Selection => self.selected_index.map(|index| {
+                let count = u64::from(self.hex_view_rows) * u64::from(self.hex_view_columns);
+                (index as u64, count)
+            }),
+            TargetRange::File => Some((0, self.max_file_len() as u64)),
+        }
+    }
+
+    fn max_file_len(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| file.data.len())
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 impl eframe::App for HexApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, Keybindings::STORAGE_KEY, &self.keybindings);
+        eframe::set_value(storage, Palettes::STORAGE_KEY, &self.palettes);
+        eframe::set_value(storage, Self::COLOR_SCHEME_STORAGE_KEY, &self.color_scheme);
+        eframe::set_value(
+            storage,
+            SavedCacheSettings::STORAGE_KEY,
+            &self.cache_settings,
+        );
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         self.frame_history
             .on_new_frame(ctx.input(|i| i.time), frame.info().cpu_usage);
 
-        ctx.input(|i| {
-            // Handle files dropped into the window: load the file and update the caches.
-            if let Some(dropped_file) = i.raw.dropped_files.first() {
-                if let Some(bytes) = &dropped_file.bytes {
-                    match self.active_file {
-                        WhichFile::File0 => {
-                            log::info!("File0 dropped: {}", dropped_file.name);
-                            self.pattern0 = Some(bytes.to_vec());
-                            self.source_name0 = Some(dropped_file.name.clone());
-                            self.cache0 = RangeBlockCache::generate(
-                                &RangeBlockSum::new(self.pattern0.as_ref().unwrap()),
-                                self.pattern0.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                            self.color_cache_value0 = RangeBlockCache::generate(
-                                &RangeBlockColorSum::new(
-                                    self.pattern0.as_ref().unwrap(),
-                                    byte_color_rgb,
-                                ),
-                                self.pattern0.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                            self.color_cache_semantic01_0 = RangeBlockCache::generate(
-                                &RangeBlockColorSum::new(
-                                    self.pattern0.as_ref().unwrap(),
-                                    semantic01_color_rgb,
-                                ),
-                                self.pattern0.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                        }
-                        WhichFile::File1 => {
-                            log::info!("File1 dropped: {}", dropped_file.name);
-                            self.pattern1 = Some(bytes.to_vec());
-                            self.source_name1 = Some(dropped_file.name.clone());
-                            self.cache1 = RangeBlockCache::generate(
-                                &RangeBlockSum::new(self.pattern1.as_ref().unwrap()),
-                                self.pattern1.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                            self.color_cache_value1 = RangeBlockCache::generate(
-                                &RangeBlockColorSum::new(
-                                    self.pattern1.as_ref().unwrap(),
-                                    byte_color_rgb,
-                                ),
-                                self.pattern1.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                            self.color_cache_semantic01_1 = RangeBlockCache::generate(
-                                &RangeBlockColorSum::new(
-                                    self.pattern1.as_ref().unwrap(),
-                                    semantic01_color_rgb,
-                                ),
-                                self.pattern1.as_ref().unwrap().len(),
-                                Self::SUB_BLOCK_SQRT,
-                            );
-                        }
-                    }
-                    if let (Some(pattern0), Some(pattern1)) = (&self.pattern0, &self.pattern1) {
-                        self.diff_cache = RangeBlockCache::generate(
-                            &RangeBlockDiff::new(pattern0, pattern1),
-                            std::cmp::max(pattern0.len(), pattern1.len()),
-                            Self::SUB_BLOCK_SQRT,
-                        );
-                    }
+        // Handle files dropped into the window: load each file as a new file and generate its caches.
+        // (or apply it as a patch to the active file, if the patch window is open)
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for dropped_file in dropped_files {
+            if let Some(bytes) = &dropped_file.bytes {
+                if self.patch_window {
+                    self.patch_status = patch_window::apply_patch(self, &dropped_file.name, bytes);
+                    continue;
                 }
+                log::info!("File{} dropped: {}", self.files.len(), dropped_file.name);
+                self.add_file(LoadedFile::new(
+                    Some(dropped_file.name.clone()),
+                    bytes.to_vec(),
+                    &self.cache_settings,
+                ));
             }
-        });
+        }
+
+        self.update_comparison_caches();
+        self.update_byte_color_caches(ctx);
+        commands::handle_shortcuts(self, ctx);
 
         // UI config options window (opened via bottom bar button).
         Window::new("UI Config")
@@ -307,6 +638,25 @@ impl eframe::App for HexApp {
                 );
                 ui.checkbox(&mut self.ui_config.selected_block, "Selected block");
                 ui.checkbox(&mut self.ui_config.cursor, "Cursor");
+
+                ui.separator();
+                let cache_settings = &mut self.cache_settings;
+                ui.checkbox(&mut cache_settings.enabled, "Save caches of large files")
+                    .on_hover_text("Saved caches are loaded instead of regenerated when a file is opened again");
+                ui.horizontal(|ui| {
+                    ui.label("Saved cache limit:");
+                    ui.add(
+                        egui::DragValue::new(&mut cache_settings.max_size_mib)
+                            .range(16..=1 << 20)
+                            .suffix(" MiB"),
+                    );
+                    if ui.button("Clear").clicked() {
+                        cache_settings.status = cache_settings.clear();
+                    }
+                });
+                if !cache_settings.status.is_empty() {
+                    ui.label(&cache_settings.status);
+                }
             });
 
         // Info window for highlighted range block at the current visible recursion level.
@@ -316,29 +666,31 @@ impl eframe::App for HexApp {
                 ui.label(format!(
                     "Selected range block: 0x{index:08X}; size: {count} bytes"
                 ));
-                if let Some(data) = &self.pattern0 {
-                    let sum0 = self
-                        .cache0
-                        .get(index, count)
-                        .unwrap_or_else(|| RangeBlockSum::new(data).value(index, count));
-                    let average0 = sum0 as f32 / count as f32;
+                for (file_index, file) in self.files.iter().enumerate() {
+                    let stats = file.stats(index, count);
+                    if stats.len == 0 {
+                        continue;
+                    }
 
-                    ui.label(format!("File0 Average byte value: {}", average0));
-                }
-                if let Some(data) = &self.pattern1 {
-                    let sum1 = self
-                        .cache1
-                        .get(index, count)
-                        .unwrap_or_else(|| RangeBlockSum::new(data).value(index, count));
-                    let average1 = sum1 as f32 / count as f32;
-                    ui.label(format!("File1 Average byte value: {}", average1));
+                    ui.label(format!(
+                        "File{file_index} Average byte value: {}; min: 0x{:02X}; max: 0x{:02X}",
+                        stats.average(),
+                        stats.min,
+                        stats.max
+                    ));
+                    ui.label(format!(
+                        "    0x00: {:.1}%; 0xFF: {:.1}%; printable: {:.1}%; entropy (estimated): {:.2}",
+                        100.0 * stats.zero_fraction(),
+                        100.0 * stats.ff_fraction(),
+                        100.0 * stats.printable_fraction(),
+                        stats.sketch_entropy()
+                    ));
                 }
 
-                if let (Some(data0), Some(data1)) = (&self.pattern0, &self.pattern1) {
-                    let diff = self
-                        .diff_cache
-                        .get(index, count)
-                        .unwrap_or_else(|| RangeBlockDiff::new(data0, data1).value(index, count));
+                if let (Some(active), Some(reference)) = (self.active(), self.reference()) {
+                    let diff = self.diff_cache.get(index, count).unwrap_or_else(|| {
+                        RangeBlockDiff::new(&active.data, &reference.data).value(index, count)
+                    });
 
                     if let Some(diff) = diff {
                         ui.label(format!(
@@ -348,9 +700,32 @@ impl eframe::App for HexApp {
                         ));
                     }
                 }
+
+                if self.reference().is_some() {
+                    let variability = self
+                        .variability_cache
+                        .get(index, count)
+                        .unwrap_or_else(|| self.variability().value(index, count));
+
+                    ui.label(format!(
+                        "Variability: {} of {} other files differ from the reference file",
+                        variability.count_ones(),
+                        self.files.len() - 1
+                    ));
+                }
             }
         });
 
+        diff_list::diff_list_window(self, ctx);
+        patch_window::patch_window(self, ctx);
+        statistics_window::statistics_window(self, ctx);
+        strings_window::strings_window(self, ctx);
+        checksum_window::checksum_window(self, ctx);
+        keybindings_window::keybindings_window(self, ctx);
+        palette_window::palette_window(self, ctx);
+        export_window::export_window(self, ctx);
+        command_palette::command_palette_window(self, ctx);
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             top_bar::top_bar(self, ctx, ui);
         });
@@ -363,8 +738,17 @@ impl eframe::App for HexApp {
             hex_view::hex_view(self, ctx, ui);
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
+        if self.minimap {
+            egui::SidePanel::right("minimap panel")
+                .default_width(200.0)
+                .show(ctx, |ui| {
+                    minimap::minimap(self, ui);
+                });
+        }
+
+        let central_panel = egui::CentralPanel::default().show(ctx, |ui| {
             main_view::main_view(self, ctx, ui);
         });
+        legend::legend(self, ctx, central_panel.response.rect);
     }
 }
@@ -0,0 +1,267 @@
+use egui::{ComboBox, Context, DragValue, Grid, RichText, ScrollArea, Ui, Window};
+use range_blocks::checksum::{
+    adler32, find_stored_value, Crc, CrcParams, HashAlgorithm, StoredValue,
+};
+
+use crate::hex_app::{HexApp, TargetRange};
+
+/// Limit on the number of stored checksum locations listed.
+const MAX_LOCATIONS: usize = 1000;
+
+/// Options and results of the checksums window.
+pub struct Checksums {
+    range: TargetRange,
+    crc: CrcParams,
+    /// The (file indices, range, CRC parameters) that `results` were calculated for.
+    calculated_for: Option<(Vec<usize>, (u64, u64), CrcParams)>,
+    results: Vec<FileChecksums>,
+    stored: Option<StoredSearch>,
+}
+
+impl Default for Checksums {
+    fn default() -> Self {
+        Self {
+            range: TargetRange::Selection,
+            crc: CrcParams::CRC_32,
+            calculated_for: None,
+            results: Vec::new(),
+            stored: None,
+        }
+    }
+}
+
+/// The checksums of one file over the window's range.
+struct FileChecksums {
+    file_index: usize,
+    /// The number of bytes checksummed: the range is clamped to the end of the file.
+    len: u64,
+    crc: u32,
+    adler32: u32,
+    hashes: Vec<(HashAlgorithm, String)>,
+}
+
+/// The locations where a checksum value is stored in a file.
+struct StoredSearch {
+    file_index: usize,
+    /// The checksum's name and value.
+    description: String,
+    locations: Vec<StoredValue>,
+}
+
+impl Checksums {
+    /// Forces the checksums to be calculated again (e.g. after file indices have shifted).
+    pub fn invalidate(&mut self) {
+        self.calculated_for = None;
+        self.stored = None;
+    }
+
+    /// Draws the CRC parameter controls.
+    fn crc_options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ComboBox::from_label("CRC")
+                .selected_text(self.crc.name().unwrap_or("Custom"))
+                .show_ui(ui, |ui| {
+                    for (name, params) in CrcParams::PRESETS {
+                        ui.selectable_value(&mut self.crc, params, name);
+                    }
+                });
+
+            let width = self.crc.width;
+            ComboBox::from_label("bits")
+                .width(40.0)
+                .selected_text(width.to_string())
+                .show_ui(ui, |ui| {
+                    for width in [8, 16, 32] {
+                        ui.selectable_value(&mut self.crc.width, width, width.to_string());
+                    }
+                });
+            if self.crc.width != width {
+                let mask = self.crc.mask();
+                self.crc.poly &= mask;
+                self.crc.init &= mask;
+                self.crc.xor_out &= mask;
+            }
+        });
+
+        let width = self.crc.width;
+        ui.horizontal(|ui| {
+            ui.label("Polynomial:");
+            ui.add(hex_value(&mut self.crc.poly, width));
+            ui.label("Init:");
+            ui.add(hex_value(&mut self.crc.init, width));
+            ui.label("XOR out:");
+            ui.add(hex_value(&mut self.crc.xor_out, width));
+            ui.checkbox(&mut self.crc.reflected, "Reflected");
+        });
+    }
+
+    /// Calculates the checksums of each file over `range`, if they haven't been already.
+    fn calculate(&mut self, files: &[(usize, &[u8])], (index, len): (u64, u64)) {
+        let file_indices = files.iter().map(|&(file_index, _)| file_index).collect();
+        let calculated_for = Some((file_indices, (index, len), self.crc));
+        if self.calculated_for == calculated_for {
+            return;
+        }
+
+        let crc = Crc::new(self.crc);
+        self.results = files
+            .iter()
+            .filter_map(|&(file_index, data)| {
+                let start = usize::try_from(index)
+                    .ok()
+                    .filter(|&start| start < data.len())?;
+                let end = usize::try_from(index.saturating_add(len))
+                    .unwrap_or(usize::MAX)
+                    .min(data.len());
+                let data = &data[start..end];
+
+                Some(FileChecksums {
+                    file_index,
+                    len: data.len() as u64,
+                    crc: crc.checksum(data),
+                    adler32: adler32(data),
+                    hashes: HashAlgorithm::ALL
+                        .iter()
+                        .map(|&algorithm| (algorithm, algorithm.hash(data)))
+                        .collect(),
+                })
+            })
+            .collect();
+        self.calculated_for = calculated_for;
+    }
+}
+
+/// A hexadecimal editor for a `width`-bit CRC parameter.
+fn hex_value(value: &mut u32, width: u32) -> DragValue<'_> {
+    DragValue::new(value)
+        .hexadecimal(width as usize / 4, false, true)
+        .range(0..=u32::MAX >> (32 - width))
+}
+
+/// Draws the window showing checksums and hashes of the active and reference files,
+/// over the same range (opened via bottom bar button).
+pub fn checksum_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.checksum_window;
+
+    Window::new("Checksums").open(&mut open).show(ctx, |ui| {
+        hex_app.checksums.range.combo_box(ui);
+        hex_app.checksums.crc_options(ui);
+
+        let Some((index, len)) = hex_app.target_range(hex_app.checksums.range) else {
+            ui.label("nothing selected");
+            return;
+        };
+        ui.label(format!("0x{index:08X}; size: {len} bytes"));
+        ui.separator();
+
+        let mut file_indices = vec![hex_app.active_file];
+        if hex_app.reference_file != hex_app.active_file {
+            file_indices.push(hex_app.reference_file);
+        }
+        let files: Vec<(usize, &[u8])> = file_indices
+            .iter()
+            .filter_map(|&file_index| {
+                let file = hex_app.files.get(file_index)?;
+                Some((file_index, file.data.as_slice()))
+            })
+            .collect();
+
+        let checksums = &mut hex_app.checksums;
+        checksums.calculate(&files, (index, len));
+
+        let crc_name = checksums.crc.name().unwrap_or("CRC");
+        let crc_digits = checksums.crc.width as usize / 4;
+        let mut find = None;
+
+        for result in &checksums.results {
+            let Some(file) = hex_app.files.get(result.file_index) else {
+                continue;
+            };
+            ui.strong(format!(
+                "{} ({} bytes)",
+                file.label(result.file_index),
+                result.len
+            ));
+
+            Grid::new(("checksum_grid", result.file_index))
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    let checksum_rows = [
+                        (crc_name, result.crc, crc_digits),
+                        ("Adler-32", result.adler32, 8),
+                    ];
+                    for (name, value, digits) in checksum_rows {
+                        let description = format!("{name} 0x{value:0digits$X}");
+                        ui.label(name);
+                        ui.monospace(format!("{value:0digits$X}"));
+                        if ui
+                            .small_button("Find")
+                            .on_hover_text("Find where this value is stored in the file")
+                            .clicked()
+                        {
+                            find = Some((result.file_index, description, value, digits / 2));
+                        }
+                        ui.end_row();
+                    }
+
+                    for (algorithm, hash) in &result.hashes {
+                        ui.label(algorithm.name());
+                        ui.monospace(hash);
+                        ui.end_row();
+                    }
+                });
+            ui.add_space(4.0);
+        }
+
+        if let Some((file_index, description, value, value_len)) = find {
+            let data = &hex_app.files[file_index].data;
+            checksums.stored = Some(StoredSearch {
+                file_index,
+                description,
+                locations: find_stored_value(data, value, value_len, MAX_LOCATIONS),
+            });
+        }
+
+        let mut go_to_index = None;
+
+        if let Some(stored) = &checksums.stored {
+            ui.separator();
+            let limit_note = if stored.locations.len() == MAX_LOCATIONS {
+                " (limit reached)"
+            } else {
+                ""
+            };
+            ui.label(format!(
+                "{} is stored at {} locations{limit_note} in File{}",
+                stored.description,
+                stored.locations.len(),
+                stored.file_index
+            ));
+
+            ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for location in &stored.locations {
+                    let endianness = if location.big_endian {
+                        "big-endian"
+                    } else {
+                        "little-endian"
+                    };
+                    let text = RichText::new(format!("0x{:08X} ({endianness})", location.index));
+                    let selected = hex_app
+                        .selected_index
+                        .is_some_and(|index| index as u64 == location.index);
+
+                    if ui.selectable_label(selected, text.monospace()).clicked() {
+                        go_to_index = Some(location.index as usize);
+                    }
+                }
+            });
+        }
+
+        if let Some(index) = go_to_index {
+            hex_app.go_to_index(index);
+        }
+    });
+
+    hex_app.checksum_window = open;
+}
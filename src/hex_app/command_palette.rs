@@ -0,0 +1,148 @@
+use egui::{Align2, Context, Key, RichText, ScrollArea, TextEdit, Window};
+
+use crate::hex_app::{commands::Command, HexApp};
+
+/// State of the command palette, which runs commands found by fuzzy search.
+#[derive(Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    /// Index of the highlighted match.
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Opens the palette with an empty query, or closes it.
+    pub fn toggle(&mut self) {
+        self.open ^= true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// The commands matching the query, best match first.
+    fn matches(&self) -> Vec<Command> {
+        let mut matches: Vec<(i32, Command)> = Command::ALL
+            .into_iter()
+            .filter(|&command| command != Command::Palette)
+            .filter_map(|command| Some((fuzzy_score(&self.query, command.name())?, command)))
+            .collect();
+        // (Stable, so equal scores keep the order of `Command::ALL`.)
+        matches.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+}
+
+/// Scores how well `query` fuzzily matches `text`, or `None` unless all of `query`'s characters
+/// (other than spaces) appear in `text` in order, ignoring case. Runs of consecutive characters
+/// and characters at the start of words score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut consecutive = false;
+
+    for c in text.chars() {
+        let Some(&q) = query.peek() else {
+            break;
+        };
+        if c.to_ascii_lowercase() == q {
+            query.next();
+            let word_start = previous.map_or(true, |p| !p.is_alphanumeric());
+            score += 1 + 5 * i32::from(consecutive) + 3 * i32::from(word_start);
+            consecutive = true;
+        } else {
+            consecutive = false;
+        }
+        previous = Some(c);
+    }
+
+    query.peek().is_none().then_some(score)
+}
+
+/// Draws the command palette (opened with Ctrl+Shift+P by default).
+pub fn command_palette_window(hex_app: &mut HexApp, ctx: &Context) {
+    if !hex_app.command_palette.open {
+        return;
+    }
+    let mut run = None;
+
+    Window::new("Command palette")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+        .show(ctx, |ui| {
+            let palette = &mut hex_app.command_palette;
+            let query = TextEdit::singleline(&mut palette.query)
+                .hint_text("Type a command")
+                .desired_width(400.0);
+            let query = ui.add(query);
+            query.request_focus();
+            if query.changed() {
+                palette.selected = 0;
+            }
+
+            let matches = palette.matches();
+            let last = matches.len().saturating_sub(1);
+            let mut moved = false;
+            ui.input_mut(|i| {
+                if i.consume_key(Default::default(), Key::ArrowDown) {
+                    palette.selected = (palette.selected + 1).min(last);
+                    moved = true;
+                }
+                if i.consume_key(Default::default(), Key::ArrowUp) {
+                    palette.selected = palette.selected.saturating_sub(1);
+                    moved = true;
+                }
+                if i.consume_key(Default::default(), Key::Enter) {
+                    run = matches.get(palette.selected).copied();
+                }
+                if i.consume_key(Default::default(), Key::Escape) {
+                    palette.open = false;
+                }
+            });
+
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for (i, &command) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let selected = i == palette.selected;
+                        let label = ui.selectable_label(selected, command.name());
+                        if selected && moved {
+                            label.scroll_to_me(None);
+                        }
+                        if label.clicked() {
+                            run = Some(command);
+                        }
+                        let shortcut = hex_app.keybindings.shortcut_text(ctx, command);
+                        ui.label(RichText::new(shortcut).weak());
+                    });
+                }
+            });
+        });
+
+    if let Some(command) = run {
+        hex_app.command_palette.open = false;
+        command.run(hex_app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching() {
+        let text = "Navigate: zoom to the selection";
+        assert!(fuzzy_score("zoom sel", text).is_some());
+        assert!(fuzzy_score("ZTS", text).is_some());
+        assert_eq!(fuzzy_score("selection zoom", text), None);
+        assert_eq!(fuzzy_score("", text), Some(0));
+
+        // Consecutive characters and word starts score higher.
+        assert!(fuzzy_score("zoom", text) > fuzzy_score("zoom", "Z o o m"));
+        assert!(fuzzy_score("ns", "Next selection") > fuzzy_score("ns", "ransom"));
+    }
+}
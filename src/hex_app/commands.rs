@@ -0,0 +1,315 @@
+use egui::{Context, Key, KeyboardShortcut, Modifiers};
+use range_blocks::color::ColorScheme;
+use serde::{Deserialize, Serialize};
+
+use crate::hex_app::{export_window, navigation, HexApp};
+
+/// Every action that can be bound to a key or run from the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Command {
+    NextActiveFile,
+    CloseActiveFile,
+    NextCellViewMode,
+    NextColorMode,
+    NextColorScheme,
+    NextDifference,
+    PreviousDifference,
+    FitToWindow,
+    ZoomToSelectedBlock,
+    ZoomToSelection,
+    LevelUp,
+    LevelDown,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    CursorPageUp,
+    CursorPageDown,
+    CursorRowStart,
+    CursorRowEnd,
+    CursorFileStart,
+    CursorFileEnd,
+    EnterBlock,
+    LeaveBlock,
+    ToggleUiConfig,
+    ToggleDifferences,
+    TogglePatch,
+    ToggleStatistics,
+    ToggleStrings,
+    ToggleChecksums,
+    ToggleMinimap,
+    Palette,
+    ToggleKeybindings,
+    TogglePalettes,
+    ToggleLegend,
+    ToggleExport,
+    Export,
+}
+
+impl Command {
+    pub const ALL: [Self; 37] = [
+        Self::NextActiveFile,
+        Self::CloseActiveFile,
+        Self::NextCellViewMode,
+        Self::NextColorMode,
+        Self::NextColorScheme,
+        Self::NextDifference,
+        Self::PreviousDifference,
+        Self::FitToWindow,
+        Self::ZoomToSelectedBlock,
+        Self::ZoomToSelection,
+        Self::LevelUp,
+        Self::LevelDown,
+        Self::CursorLeft,
+        Self::CursorRight,
+        Self::CursorUp,
+        Self::CursorDown,
+        Self::CursorPageUp,
+        Self::CursorPageDown,
+        Self::CursorRowStart,
+        Self::CursorRowEnd,
+        Self::CursorFileStart,
+        Self::CursorFileEnd,
+        Self::EnterBlock,
+        Self::LeaveBlock,
+        Self::ToggleUiConfig,
+        Self::ToggleDifferences,
+        Self::TogglePatch,
+        Self::ToggleStatistics,
+        Self::ToggleStrings,
+        Self::ToggleChecksums,
+        Self::ToggleMinimap,
+        Self::Palette,
+        Self::ToggleKeybindings,
+        Self::TogglePalettes,
+        Self::ToggleLegend,
+        Self::ToggleExport,
+        Self::Export,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NextActiveFile => "File: switch to the next file",
+            Self::CloseActiveFile => "File: close the active file",
+            Self::NextCellViewMode => "View: next cell view mode",
+            Self::NextColorMode => "View: next color mode",
+            Self::NextColorScheme => "View: next color scheme (colorblind-safe)",
+            Self::NextDifference => "Navigate: next difference",
+            Self::PreviousDifference => "Navigate: previous difference",
+            Self::FitToWindow => "Navigate: fit the file",
+            Self::ZoomToSelectedBlock => "Navigate: zoom to the selected block",
+            Self::ZoomToSelection => "Navigate: zoom to the selection",
+            Self::LevelUp => "Navigate: up one level",
+            Self::LevelDown => "Navigate: down one level",
+            Self::CursorLeft => "Cursor: left",
+            Self::CursorRight => "Cursor: right",
+            Self::CursorUp => "Cursor: up",
+            Self::CursorDown => "Cursor: down",
+            Self::CursorPageUp => "Cursor: page up",
+            Self::CursorPageDown => "Cursor: page down",
+            Self::CursorRowStart => "Cursor: start of row",
+            Self::CursorRowEnd => "Cursor: end of row",
+            Self::CursorFileStart => "Cursor: start of file",
+            Self::CursorFileEnd => "Cursor: end of file",
+            Self::EnterBlock => "Cursor: enter block",
+            Self::LeaveBlock => "Cursor: leave block",
+            Self::ToggleUiConfig => "Window: UI config",
+            Self::ToggleDifferences => "Window: differences",
+            Self::TogglePatch => "Window: patch",
+            Self::ToggleStatistics => "Window: statistics",
+            Self::ToggleStrings => "Window: strings (search)",
+            Self::ToggleChecksums => "Window: checksums",
+            Self::ToggleMinimap => "Window: minimap",
+            Self::Palette => "Command palette",
+            Self::ToggleKeybindings => "Window: keybindings",
+            Self::TogglePalettes => "Window: palettes",
+            Self::ToggleLegend => "View: color legend",
+            Self::ToggleExport => "Window: export view",
+            Self::Export => "Export: save the image again with the export window's settings",
+        }
+    }
+
+    pub fn run(self, hex_app: &mut HexApp) {
+        let columns = usize::from(hex_app.hex_view_columns);
+        let page = columns * usize::from(hex_app.hex_view_rows);
+
+        match self {
+            Self::NextActiveFile => hex_app.next_active_file(),
+            Self::CloseActiveFile => hex_app.close_active_file(),
+            Self::NextCellViewMode => hex_app.cell_view_mode = hex_app.cell_view_mode.next(),
+            Self::NextColorMode => hex_app.color_mode = hex_app.color_mode.next(),
+            Self::NextColorScheme => {
+                let schemes = ColorScheme::ALL;
+                let index = schemes.iter().position(|&s| s == hex_app.color_scheme);
+                hex_app.color_scheme = schemes[index.map_or(0, |i| (i + 1) % schemes.len())];
+            }
+            Self::NextDifference => hex_app.next_difference(),
+            Self::PreviousDifference => hex_app.previous_difference(),
+            Self::FitToWindow => navigation::fit_to_window(hex_app),
+            Self::ZoomToSelectedBlock => navigation::zoom_to_selected_block(hex_app),
+            Self::ZoomToSelection => navigation::zoom_to_selection(hex_app),
+            Self::LevelUp => navigation::step_recursion_level(hex_app, 1),
+            Self::LevelDown => navigation::step_recursion_level(hex_app, -1),
+            Self::CursorLeft => navigation::move_cursor(hex_app, (-1, 0)),
+            Self::CursorRight => navigation::move_cursor(hex_app, (1, 0)),
+            Self::CursorUp => navigation::move_cursor(hex_app, (0, -1)),
+            Self::CursorDown => navigation::move_cursor(hex_app, (0, 1)),
+            Self::CursorPageUp => navigation::set_cursor(hex_app, |i| i.saturating_sub(page)),
+            Self::CursorPageDown => navigation::set_cursor(hex_app, |i| i + page),
+            Self::CursorRowStart => navigation::set_cursor(hex_app, |i| i - i % columns),
+            Self::CursorRowEnd => {
+                navigation::set_cursor(hex_app, |i| i - i % columns + columns - 1)
+            }
+            Self::CursorFileStart => navigation::set_cursor(hex_app, |_| 0),
+            Self::CursorFileEnd => navigation::set_cursor(hex_app, |_| usize::MAX),
+            Self::EnterBlock => navigation::enter_block(hex_app),
+            Self::LeaveBlock => navigation::leave_block(hex_app),
+            Self::ToggleUiConfig => hex_app.ui_config_window ^= true,
+            Self::ToggleDifferences => hex_app.diff_list_window ^= true,
+            Self::TogglePatch => hex_app.patch_window ^= true,
+            Self::ToggleStatistics => hex_app.statistics_window ^= true,
+            Self::ToggleStrings => hex_app.strings_window ^= true,
+            Self::ToggleChecksums => hex_app.checksum_window ^= true,
+            Self::ToggleMinimap => hex_app.minimap ^= true,
+            Self::Palette => hex_app.command_palette.toggle(),
+            Self::ToggleKeybindings => hex_app.keybindings_window ^= true,
+            Self::TogglePalettes => hex_app.palette_window ^= true,
+            Self::ToggleLegend => hex_app.legend ^= true,
+            Self::ToggleExport => hex_app.export_window ^= true,
+            Self::Export => export_window::export_again(hex_app),
+        }
+    }
+}
+
+/// The keyboard shortcuts bound to each command (saved with the app settings).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keybindings {
+    bindings: Vec<(Command, KeyboardShortcut)>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let shortcut = KeyboardShortcut::new;
+        let none = Modifiers::NONE;
+
+        Self {
+            bindings: vec![
+                (Command::NextActiveFile, shortcut(none, Key::F)),
+                (Command::NextCellViewMode, shortcut(none, Key::V)),
+                (Command::NextColorMode, shortcut(none, Key::C)),
+                (Command::NextDifference, shortcut(none, Key::N)),
+                (
+                    Command::PreviousDifference,
+                    shortcut(Modifiers::SHIFT, Key::N),
+                ),
+                (Command::FitToWindow, shortcut(none, Key::Num0)),
+                (Command::ZoomToSelectedBlock, shortcut(none, Key::B)),
+                (Command::ZoomToSelection, shortcut(none, Key::S)),
+                (Command::LevelUp, shortcut(none, Key::Minus)),
+                (Command::LevelDown, shortcut(none, Key::Equals)),
+                (Command::CursorLeft, shortcut(none, Key::ArrowLeft)),
+                (Command::CursorRight, shortcut(none, Key::ArrowRight)),
+                (Command::CursorUp, shortcut(none, Key::ArrowUp)),
+                (Command::CursorDown, shortcut(none, Key::ArrowDown)),
+                (Command::CursorPageUp, shortcut(none, Key::PageUp)),
+                (Command::CursorPageDown, shortcut(none, Key::PageDown)),
+                (Command::CursorRowStart, shortcut(none, Key::Home)),
+                (Command::CursorRowEnd, shortcut(none, Key::End)),
+                (
+                    Command::CursorFileStart,
+                    shortcut(Modifiers::COMMAND, Key::Home),
+                ),
+                (
+                    Command::CursorFileEnd,
+                    shortcut(Modifiers::COMMAND, Key::End),
+                ),
+                (Command::EnterBlock, shortcut(none, Key::Enter)),
+                (Command::LeaveBlock, shortcut(none, Key::Backspace)),
+                (
+                    Command::Palette,
+                    shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::P),
+                ),
+            ],
+        }
+    }
+}
+
+impl Keybindings {
+    /// The key that the keybindings are saved under in eframe's storage.
+    pub const STORAGE_KEY: &'static str = "keybindings";
+
+    /// The shortcuts bound to `command`.
+    pub fn shortcuts(&self, command: Command) -> impl Iterator<Item = KeyboardShortcut> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(c, _)| c == command)
+            .map(|&(_, shortcut)| shortcut)
+    }
+
+    /// Binds `shortcut` to `command`, unbinding it from any other command.
+    pub fn bind(&mut self, command: Command, shortcut: KeyboardShortcut) {
+        self.bindings.retain(|&(_, s)| s != shortcut);
+        self.bindings.push((command, shortcut));
+    }
+
+    /// Removes all of `command`'s shortcuts.
+    pub fn unbind(&mut self, command: Command) {
+        self.bindings.retain(|&(c, _)| c != command);
+    }
+
+    /// `command`'s shortcuts, formatted for display (e.g. "Ctrl+Shift+P").
+    pub fn shortcut_text(&self, ctx: &Context, command: Command) -> String {
+        let texts: Vec<String> = self
+            .shortcuts(command)
+            .map(|shortcut| ctx.format_shortcut(&shortcut))
+            .collect();
+        texts.join(", ")
+    }
+
+    /// Hover text describing `command` and its shortcuts.
+    pub fn hover_text(&self, ctx: &Context, command: Command) -> String {
+        let shortcut_text = self.shortcut_text(ctx, command);
+        if shortcut_text.is_empty() {
+            command.name().to_string()
+        } else {
+            format!("{} ({shortcut_text})", command.name())
+        }
+    }
+}
+
+/// Runs the commands whose shortcuts were pressed this frame
+/// (unless a widget like a text field is using the keyboard).
+pub fn handle_shortcuts(hex_app: &mut HexApp, ctx: &Context) {
+    if ctx.wants_keyboard_input() || hex_app.keybinding_capture.is_some() {
+        return;
+    }
+
+    // Shortcuts with more modifiers come first, so that e.g. Shift+N isn't taken as N.
+    let mut bindings = hex_app.keybindings.bindings.clone();
+    bindings.sort_by_key(|(_, shortcut)| {
+        let Modifiers {
+            alt,
+            ctrl,
+            shift,
+            mac_cmd,
+            command,
+        } = shortcut.modifiers;
+        std::cmp::Reverse(
+            [alt, ctrl || command || mac_cmd, shift]
+                .map(u8::from)
+                .iter()
+                .sum::<u8>(),
+        )
+    });
+
+    let commands: Vec<Command> = ctx.input_mut(|i| {
+        bindings
+            .into_iter()
+            .filter(|(_, shortcut)| i.consume_shortcut(shortcut))
+            .map(|(command, _)| command)
+            .collect()
+    });
+    for command in commands {
+        command.run(hex_app);
+    }
+}
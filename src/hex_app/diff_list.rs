@@ -0,0 +1,104 @@
+use egui::{Context, Grid, RichText, ScrollArea, TextStyle, Window};
+
+use crate::hex_app::{commands::Command, HexApp};
+
+/// Number of bytes from each file shown in a difference list entry's preview.
+const PREVIEW_LEN: usize = 8;
+
+/// Draws the window listing contiguous differing runs between the active and reference files
+/// (opened via bottom bar button).
+pub fn diff_list_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.diff_list_window;
+
+    Window::new("Differences").open(&mut open).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui
+                .button("⏴ Previous")
+                .on_hover_text(
+                    hex_app
+                        .keybindings
+                        .hover_text(ctx, Command::PreviousDifference),
+                )
+                .clicked()
+            {
+                hex_app.previous_difference();
+            }
+            if ui
+                .button("Next ⏵")
+                .on_hover_text(hex_app.keybindings.hover_text(ctx, Command::NextDifference))
+                .clicked()
+            {
+                hex_app.next_difference();
+            }
+        });
+
+        let (Some(active), Some(reference)) = (hex_app.active(), hex_app.reference()) else {
+            ui.label("no files to compare");
+            return;
+        };
+
+        let run_count = hex_app.diff_runs.len();
+        let limit_note = if run_count == HexApp::MAX_DIFF_RUNS {
+            " (limit reached)"
+        } else {
+            ""
+        };
+        ui.label(format!(
+            "{run_count} differing runs{limit_note} between {} and {}",
+            active.label(hex_app.active_file),
+            reference.label(hex_app.reference_file)
+        ));
+        ui.separator();
+
+        let preview = |data: &[u8], index: u64, len: u64| -> String {
+            let start = index as usize;
+            let end = (start + (len as usize).min(PREVIEW_LEN)).min(data.len());
+            let mut text = data[start..end]
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if len as usize > PREVIEW_LEN {
+                text += " …";
+            }
+            text
+        };
+
+        let mut go_to_index = None;
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+
+        ScrollArea::vertical().show_rows(ui, row_height, run_count, |ui, row_range| {
+            Grid::new("diff_list_grid")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.strong("Offset");
+                    ui.strong("Length");
+                    ui.strong(format!("File{}", hex_app.active_file));
+                    ui.strong(format!("File{}", hex_app.reference_file));
+                    ui.end_row();
+
+                    for run in &hex_app.diff_runs[row_range] {
+                        let selected = hex_app
+                            .selected_index
+                            .is_some_and(|index| index as u64 == run.index);
+                        let offset = RichText::new(format!("0x{:08X}", run.index)).monospace();
+
+                        if ui.selectable_label(selected, offset).clicked() {
+                            go_to_index = Some(run.index as usize);
+                        }
+                        ui.monospace(run.len.to_string());
+                        ui.monospace(preview(&active.data, run.index, run.len));
+                        ui.monospace(preview(&reference.data, run.index, run.len));
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(index) = go_to_index {
+            hex_app.go_to_index(index);
+        }
+    });
+
+    hex_app.diff_list_window = open;
+}
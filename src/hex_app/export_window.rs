@@ -0,0 +1,340 @@
+use egui::{Context, DragValue, Window};
+use range_blocks::{
+    color::Rgb,
+    max_recursion_level,
+    render::{render_view, View},
+    svg::{render_svg, Outline, SvgOptions},
+    CompleteLargestRangeBlockIterator,
+};
+
+use crate::{
+    hex_app::{main_view, navigation, HexApp},
+    utilities::to_rgb,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ExportFormat {
+    Png,
+    Svg,
+}
+
+impl ExportFormat {
+    const ALL: [Self; 2] = [Self::Png, Self::Svg];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Svg => "SVG",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// What part of the active file is exported.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ExportScope {
+    /// The area visible in (the first view of) the main view, at its rendered recursion level.
+    View,
+    /// The whole file, at a chosen recursion level.
+    File,
+}
+
+/// Settings of the export window, which saves range block maps as images.
+pub struct Export {
+    format: ExportFormat,
+    scope: ExportScope,
+    /// Image pixels per main view point, when exporting the view.
+    view_scale: f32,
+    /// The recursion level, when exporting the whole file.
+    file_recursion_level: u32,
+    /// The width and height of each range block in pixels, when exporting the whole file.
+    block_pixels: u32,
+    /// Outlines the selection (SVG only).
+    selection_outline: bool,
+    /// Labels range blocks with their addresses (SVG only).
+    address_labels: bool,
+    path: String,
+    /// An existing file the user chose to replace.
+    overwrite_path: Option<String>,
+    /// An existing file that an export stopped at, to offer overwriting it.
+    existing_path: Option<String>,
+    /// Whether an export has been started from the window, so the export command
+    /// uses settings the user has seen.
+    confirmed: bool,
+    status: String,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Png,
+            scope: ExportScope::View,
+            view_scale: 2.0,
+            file_recursion_level: 1,
+            block_pixels: 8,
+            selection_outline: true,
+            address_labels: false,
+            path: String::new(),
+            overwrite_path: None,
+            existing_path: None,
+            confirmed: false,
+            status: String::new(),
+        }
+    }
+}
+
+/// Draws the export window (opened via bottom bar button).
+pub fn export_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.export_window;
+
+    Window::new("Export view").open(&mut open).show(ctx, |ui| {
+        let max_recursion_level = hex_app.active().map_or(0, |file| {
+            max_recursion_level(file.data.len() as u64, HexApp::SUB_BLOCK_SQRT)
+        });
+        let image_size = match image_view(hex_app) {
+            Ok((view, _)) => format!("Image size: {} x {}", view.width, view.height),
+            Err(e) => e,
+        };
+        let settings = &mut hex_app.export;
+
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            for format in ExportFormat::ALL {
+                ui.selectable_value(&mut settings.format, format, format.name());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Area:");
+            ui.selectable_value(&mut settings.scope, ExportScope::View, "Main view");
+            ui.selectable_value(&mut settings.scope, ExportScope::File, "Whole file");
+        });
+
+        match settings.scope {
+            ExportScope::View => {
+                ui.horizontal(|ui| {
+                    ui.label("Scale:");
+                    ui.add(
+                        DragValue::new(&mut settings.view_scale)
+                            .speed(0.05)
+                            .range(0.25..=16.0)
+                            .suffix("x"),
+                    );
+                });
+            }
+            ExportScope::File => {
+                ui.horizontal(|ui| {
+                    ui.label("Recursion level:");
+                    ui.add(
+                        DragValue::new(&mut settings.file_recursion_level)
+                            .range(0..=max_recursion_level),
+                    );
+                    ui.label("Block size:");
+                    ui.add(
+                        DragValue::new(&mut settings.block_pixels)
+                            .range(1..=4096)
+                            .suffix(" px"),
+                    );
+                });
+            }
+        }
+        ui.label(image_size);
+
+        ui.add_enabled_ui(settings.format == ExportFormat::Svg, |ui| {
+            ui.checkbox(&mut settings.selection_outline, "Selection outline");
+            ui.checkbox(&mut settings.address_labels, "Address labels");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Path:");
+            ui.text_edit_singleline(&mut settings.path);
+        });
+        if ui.button("Export").clicked() {
+            hex_app.export.confirmed = true;
+            export(hex_app);
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(&hex_app.export.status);
+            let settings = &mut hex_app.export;
+            if settings.existing_path.as_ref() == Some(&settings.path)
+                && ui.button("Overwrite").clicked()
+            {
+                settings.overwrite_path = settings.existing_path.take();
+                export(hex_app);
+            }
+        });
+    });
+
+    hex_app.export_window = open;
+}
+
+/// The view of the cell plane to export, and the recursion level to draw.
+fn image_view(hex_app: &HexApp) -> Result<(View, u32), String> {
+    let Some(file) = hex_app.active() else {
+        return Err("no files loaded".to_string());
+    };
+    let data_len = file.data.len() as u64;
+    let settings = &hex_app.export;
+
+    match settings.scope {
+        ExportScope::View => {
+            let scale = settings.view_scale;
+            // (The inverse of the main view's cell to screen mapping, at the view's top left.)
+            let origin = (-hex_app.main_view_size / 2.0 - hex_app.pan) / hex_app.zoom;
+            let size = hex_app.main_view_size * scale;
+            let view = View {
+                origin: (f64::from(origin.x), f64::from(origin.y)),
+                cell_pixels: f64::from(hex_app.zoom * scale),
+                width: size.x.round() as u32,
+                height: size.y.round() as u32,
+            };
+            let recursion_level = navigation::rendered_recursion_level(hex_app).unwrap_or(0);
+            Ok((view, recursion_level))
+        }
+        ExportScope::File => {
+            let recursion_level = settings
+                .file_recursion_level
+                .min(max_recursion_level(data_len, HexApp::SUB_BLOCK_SQRT));
+            let block_cells = HexApp::SUB_BLOCK_SQRT.pow(recursion_level);
+            let cell_pixels = f64::from(settings.block_pixels) / block_cells as f64;
+            let view = View::whole_file(data_len, HexApp::SUB_BLOCK_SQRT, cell_pixels)
+                .map_err(|e| e.to_string())?;
+            Ok((view, recursion_level))
+        }
+    }
+}
+
+/// Exports an image with the current settings, and shows the result in the export window.
+/// Existing files are only replaced once the user has chosen to overwrite them.
+pub fn export(hex_app: &mut HexApp) {
+    let settings = &mut hex_app.export;
+    settings.existing_path = None;
+    if settings.path.trim().is_empty() {
+        settings.status = format!(
+            "Export failed: no path (e.g. range_blocks.{})",
+            settings.format.extension()
+        );
+        return;
+    }
+    if settings.overwrite_path.as_ref() != Some(&settings.path)
+        && std::path::Path::new(&settings.path).exists()
+    {
+        settings.status = format!("{} already exists", settings.path);
+        settings.existing_path = Some(settings.path.clone());
+        return;
+    }
+
+    hex_app.export.status = write_image(hex_app);
+}
+
+/// Exports an image with the settings last exported from the export window (which is
+/// opened). Before any export from the window, only opens it.
+pub fn export_again(hex_app: &mut HexApp) {
+    if hex_app.export.confirmed {
+        export(hex_app);
+    } else {
+        hex_app.export.status = "Choose the export settings, then export".to_string();
+    }
+    hex_app.export_window = true;
+}
+
+/// Writes the active file's range blocks, in the current color mode, as an image
+/// at the export path. Returns a status message.
+fn write_image(hex_app: &HexApp) -> String {
+    let (view, recursion_level) = match image_view(hex_app) {
+        Ok(image_view) => image_view,
+        Err(e) => return format!("Export failed: {e}"),
+    };
+    let data_len = hex_app.active().map_or(0, |file| file.data.len() as u64);
+    let settings = &hex_app.export;
+
+    let block_colors = main_view::BlockColors::new(hex_app);
+    let block_color = |index, count| -> Rgb {
+        to_rgb(block_colors.color(hex_app.active_file, hex_app.reference_file, index, count))
+    };
+    let sub_block_sqrt = HexApp::SUB_BLOCK_SQRT;
+
+    let contents = match settings.format {
+        ExportFormat::Png => {
+            let image = match render_view(
+                data_len,
+                &view,
+                recursion_level,
+                sub_block_sqrt,
+                block_color,
+            ) {
+                Ok(image) => image,
+                Err(e) => return format!("Export failed: {e}"),
+            };
+            let mut png = Vec::new();
+            if let Err(e) = image.write_png(&mut png) {
+                return format!("Export failed: {e}");
+            }
+            png
+        }
+        ExportFormat::Svg => {
+            let mut options = SvgOptions {
+                outlines: Vec::new(),
+                address_labels: settings.address_labels,
+            };
+            if let (true, Some(index)) = (settings.selection_outline, hex_app.selected_index) {
+                let index = index as u64;
+                let count = u64::from(hex_app.hex_view_rows) * u64::from(hex_app.hex_view_columns);
+                options.outlines.push(Outline {
+                    range_blocks: CompleteLargestRangeBlockIterator::new(
+                        index,
+                        index + count,
+                        max_recursion_level(data_len, sub_block_sqrt),
+                        sub_block_sqrt,
+                    )
+                    .collect(),
+                    // The main view's selection border color.
+                    color: Rgb::new(0, 255, 255),
+                    width: 2.0,
+                });
+            }
+
+            match render_svg(
+                data_len,
+                &view,
+                recursion_level,
+                sub_block_sqrt,
+                block_color,
+                &options,
+            ) {
+                Ok(svg) => svg.into_bytes(),
+                Err(e) => return format!("Export failed: {e}"),
+            }
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = &settings.path;
+        match std::fs::write(path, &contents) {
+            Ok(()) => format!(
+                "Wrote {} x {} {} to {path}",
+                view.width,
+                view.height,
+                settings.format.name()
+            ),
+            Err(e) => format!("Export failed: {e}"),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        format!(
+            "Created {} byte {}, but saving files isn't supported on the web",
+            contents.len(),
+            settings.format.name()
+        )
+    }
+}
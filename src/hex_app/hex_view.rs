@@ -1,14 +1,26 @@
 use crate::{
-    hex_app::{byte_text, ColorMode, HexApp, WhichFile},
-    utilities::{byte_color, contrast, diff_at_index, diff_color, semantic01_color},
+    hex_app::{byte_text, ColorMode, HexApp},
+    utilities::{
+        diff_at_index, diff_color, entropy_color, padding_color, printable_color, range_color,
+        text_color, to_color32, uniform_color, variability_color,
+    },
 };
-use egui::{Context, RichText, TextStyle, Ui};
+use egui::{Color32, Context, RichText, TextStyle, Ui};
+use range_blocks::{stats::BlockStats, Cacheable};
 
 /// Draws the traditional hex editor view in the left side panel.
 pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
+    if ui.ui_contains_pointer()
+        && ui.input(|i| i.pointer.any_pressed() || i.raw_scroll_delta.y != 0.0)
+    {
+        hex_app.main_view_focus = false;
+    }
+
     ui.horizontal(|ui| {
         ui.label("hex view");
         ui.checkbox(&mut hex_app.hex_view_color_mode, "colored text");
+        ui.checkbox(&mut hex_app.hex_view_dual, "dual view")
+            .on_hover_text("Show the active and reference files side by side");
     });
     ui.separator();
 
@@ -19,6 +31,9 @@ pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
 
         let mut dummy_string = "00000000: ".to_string();
         (0..hex_app.hex_view_columns).for_each(|_| dummy_string += "00 ");
+        if hex_app.hex_view_dual {
+            dummy_string = format!("{dummy_string}| {dummy_string}");
+        }
 
         ui.monospace(dummy_string);
     });
@@ -27,14 +42,14 @@ pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
         ui.label(format!("selected index: 0x{:08X}", index));
         ui.spacing_mut().item_spacing.y = -1.0;
 
-        let data = match hex_app.active_file {
-            WhichFile::File0 => &hex_app.pattern0,
-            WhichFile::File1 => &hex_app.pattern1,
-        };
-        let other_data = match hex_app.active_file {
-            WhichFile::File0 => &hex_app.pattern1,
-            WhichFile::File1 => &hex_app.pattern0,
-        };
+        let data = hex_app
+            .files
+            .get(hex_app.active_file)
+            .map(|file| &file.data);
+        let other_data = hex_app
+            .files
+            .get(hex_app.reference_file)
+            .map(|file| file.data.as_slice());
 
         let columns_isize = isize::from(hex_app.hex_view_columns);
         let columns = usize::from(hex_app.hex_view_columns);
@@ -55,7 +70,71 @@ pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
                 }
             }
 
-            if hex_app.hex_view_color_mode {
+            let scheme = hex_app.color_scheme;
+            // (Built once, not per byte.)
+            let variability = (hex_app.color_mode == ColorMode::Variability
+                && hex_app.reference().is_some())
+            .then(|| hex_app.variability());
+            let byte_color =
+                |data: &[u8], other_data: Option<&[u8]>, offset: usize| match hex_app.color_mode {
+                    ColorMode::Value | ColorMode::Semantic01 | ColorMode::Custom => {
+                        to_color32(hex_app.byte_color_table[data[offset] as usize])
+                    }
+                    ColorMode::Diff => {
+                        let diff_bytes = diff_at_index(&Some(data), &other_data, offset);
+
+                        diff_color(scheme, diff_bytes, 1)
+                    }
+                    ColorMode::Variability => {
+                        let variability = variability
+                            .as_ref()
+                            .map_or(0, |variability| variability.value(offset as u64, 1));
+                        variability_color(scheme, variability.count_ones(), hex_app.files.len())
+                    }
+                    ColorMode::Printable => printable_color(
+                        scheme,
+                        BlockStats::new(&data[offset..=offset]).printable_fraction(),
+                    ),
+                    // A single byte has no entropy.
+                    ColorMode::Entropy => entropy_color(scheme, 0.0),
+                    ColorMode::Range => range_color(scheme, 0),
+                    ColorMode::Padding => padding_color(
+                        scheme,
+                        f32::from(u8::from(data[offset] == 0x00)),
+                        f32::from(u8::from(data[offset] == 0xFF)),
+                    ),
+                    ColorMode::Uniform => uniform_color(scheme, Some(data[offset])),
+                };
+
+            if hex_app.hex_view_dual {
+                // Render the active and reference files in lockstep, highlighting differing bytes.
+                if let Some(other_data) = other_data {
+                    for i in 0..hex_app.hex_view_rows {
+                        let line_index = index + usize::from(i) * columns;
+                        let other_line_index = reference_index(line_index);
+
+                        ui.horizontal(|ui| {
+                            hex_view_dual_row(
+                                hex_app,
+                                ui,
+                                (data, line_index),
+                                (other_data, other_line_index),
+                                |offset| byte_color(data, Some(other_data), offset),
+                            );
+                            ui.monospace("|");
+                            hex_view_dual_row(
+                                hex_app,
+                                ui,
+                                (other_data, other_line_index),
+                                (data, line_index),
+                                |offset| byte_color(other_data, Some(data), offset),
+                            );
+                        });
+                    }
+                } else {
+                    ui.label("no reference file");
+                }
+            } else if hex_app.hex_view_color_mode {
                 //Render text with coloring from the UI's selected `ColorMode`.
                 for i in 0..hex_app.hex_view_rows {
                     let line_index = index + usize::from(i) * columns;
@@ -75,22 +154,13 @@ pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
                                 .monospace(),
                         );
                         while offset < data.len() && offset < line_index + columns {
-                            let color = match hex_app.color_mode {
-                                ColorMode::Value => byte_color(data[offset]),
-                                ColorMode::Diff => {
-                                    let diff_bytes =
-                                        diff_at_index(&Some(data.as_ref()), other_data, offset);
-
-                                    diff_color(diff_bytes, 1)
-                                }
-                                ColorMode::Semantic01 => semantic01_color(data[offset]),
-                            };
+                            let color = byte_color(data, other_data, offset);
 
                             let text =
                                 format!("{:2}", byte_text(data[offset], hex_app.cell_view_mode));
                             ui.label(
                                 RichText::new(text)
-                                    .color(contrast(color))
+                                    .color(text_color(color))
                                     .background_color(color)
                                     .monospace(),
                             );
@@ -120,3 +190,54 @@ pub fn hex_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
         ui.label("no index selected");
     }
 }
+
+/// The reference file index shown alongside `index` of the active file in the dual hex view.
+///
+/// This is the identity mapping for now: an alignment-aware diff would replace it
+/// with its offset mapping, so that shifted regions still line up.
+fn reference_index(index: usize) -> usize {
+    index
+}
+
+/// Draws one row of one column of the dual hex view: `data` starting at `line_index`,
+/// with bytes that differ from `other_data` (starting at `other_line_index`) highlighted.
+fn hex_view_dual_row(
+    hex_app: &HexApp,
+    ui: &mut Ui,
+    (data, line_index): (&[u8], usize),
+    (other_data, other_line_index): (&[u8], usize),
+    byte_color: impl Fn(usize) -> Color32,
+) {
+    let columns = usize::from(hex_app.hex_view_columns);
+
+    // Trick so we don't have to add spaces in the text below:
+    let width = ui.fonts(|f| f.glyph_width(&TextStyle::Monospace.resolve(ui.style()), ' '));
+    ui.spacing_mut().item_spacing.x = width - 0.25;
+    ui.label(RichText::new(format!("{:08X}:", line_index)).monospace());
+
+    for column in 0..columns {
+        let offset = line_index + column;
+        let Some(&byte) = data.get(offset) else {
+            // Keep the other column aligned past the end of this file.
+            ui.label(RichText::new("  ").monospace());
+            continue;
+        };
+        let differs = other_data.get(other_line_index + column) != Some(&byte);
+
+        let text = format!("{:2}", byte_text(byte, hex_app.cell_view_mode));
+        let mut text = RichText::new(text).monospace();
+
+        if hex_app.hex_view_color_mode {
+            let color = byte_color(offset);
+            text = text.color(text_color(color)).background_color(color);
+            if differs {
+                text = text.underline().strong();
+            }
+        } else if differs {
+            let color = diff_color(hex_app.color_scheme, Some(1), 1);
+            text = text.color(text_color(color)).background_color(color);
+        }
+
+        ui.label(text);
+    }
+}
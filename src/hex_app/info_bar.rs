@@ -5,6 +5,16 @@ pub fn info_bar(hex_app: &mut crate::hex_app::HexApp, ui: &mut Ui) {
     ui.horizontal(|ui| {
         ui.checkbox(&mut hex_app.dbg_flag, "dbg_flag");
         ui.toggle_value(&mut hex_app.ui_config_window, "UI Config");
+        ui.toggle_value(&mut hex_app.diff_list_window, "Differences");
+        ui.toggle_value(&mut hex_app.patch_window, "Patch");
+        ui.toggle_value(&mut hex_app.statistics_window, "Statistics");
+        ui.toggle_value(&mut hex_app.strings_window, "Strings");
+        ui.toggle_value(&mut hex_app.checksum_window, "Checksums");
+        ui.toggle_value(&mut hex_app.minimap, "Minimap");
+        ui.toggle_value(&mut hex_app.keybindings_window, "Keybindings");
+        ui.toggle_value(&mut hex_app.palette_window, "Palettes");
+        ui.toggle_value(&mut hex_app.legend, "Legend");
+        ui.toggle_value(&mut hex_app.export_window, "Export");
         ui.label(format!("FPS: {0:.1}", hex_app.frame_history.fps()));
         ui.label(format!("self.zoom: {}", hex_app.zoom));
         ui.label(format!("self.pan: {:?}", hex_app.pan));
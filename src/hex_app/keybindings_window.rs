@@ -0,0 +1,67 @@
+use egui::{Context, Event, Grid, Key, KeyboardShortcut, ScrollArea, Window};
+
+use crate::hex_app::{
+    commands::{Command, Keybindings},
+    HexApp,
+};
+
+/// Draws the window for remapping each command's keyboard shortcut (opened via bottom bar button).
+pub fn keybindings_window(hex_app: &mut HexApp, ctx: &Context) {
+    // The next key pressed (other than Escape, which cancels) is bound to the command being set.
+    if let Some(command) = hex_app.keybinding_capture {
+        let pressed = ctx.input(|i| {
+            i.events.iter().find_map(|event| match *event {
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some(KeyboardShortcut::new(modifiers, key)),
+                _ => None,
+            })
+        });
+        if let Some(shortcut) = pressed {
+            if shortcut.logical_key != Key::Escape {
+                hex_app.keybindings.bind(command, shortcut);
+            }
+            hex_app.keybinding_capture = None;
+        }
+    }
+
+    let mut open = hex_app.keybindings_window;
+
+    Window::new("Keybindings").open(&mut open).show(ctx, |ui| {
+        ui.label("Setting a shortcut that's already in use moves it to the new command.");
+        if ui.button("Reset to defaults").clicked() {
+            hex_app.keybindings = Keybindings::default();
+        }
+        ui.separator();
+
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("keybindings_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for command in Command::ALL {
+                        ui.label(command.name());
+                        ui.monospace(hex_app.keybindings.shortcut_text(ctx, command));
+
+                        let capturing = hex_app.keybinding_capture == Some(command);
+                        let text = if capturing { "Press a key…" } else { "Set" };
+                        if ui.selectable_label(capturing, text).clicked() {
+                            hex_app.keybinding_capture = (!capturing).then_some(command);
+                        }
+                        if ui.button("Clear").clicked() {
+                            hex_app.keybindings.unbind(command);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    });
+
+    hex_app.keybindings_window = open;
+    if !open {
+        hex_app.keybinding_capture = None;
+    }
+}
@@ -0,0 +1,197 @@
+use egui::{vec2, Align2, Area, Color32, Context, Frame, Id, Rect, RichText, Sense, Ui};
+
+use range_blocks::color::ColorScheme;
+
+use crate::{
+    hex_app::{ColorMode, HexApp},
+    utilities::to_color32,
+};
+
+/// One line of the legend.
+enum LegendItem {
+    /// A color and what it means.
+    Swatch(Color32, &'static str),
+    /// A color ramp, with what its ends mean.
+    Ramp(Vec<Color32>, &'static str, &'static str),
+}
+
+/// The number of colors sampled for each ramp.
+const RAMP_SAMPLES: usize = 64;
+
+/// Samples `color` at `RAMP_SAMPLES` evenly spaced fractions from 0.0 to 1.0.
+fn ramp(color: impl Fn(f32) -> Color32) -> Vec<Color32> {
+    (0..RAMP_SAMPLES)
+        .map(|i| color(i as f32 / (RAMP_SAMPLES - 1) as f32))
+        .collect()
+}
+
+/// A title and the items explaining the current color mode's colors.
+fn legend_items(hex_app: &HexApp) -> (String, Vec<LegendItem>) {
+    use LegendItem::{Ramp, Swatch};
+
+    let scheme = hex_app.color_scheme;
+    let table = |byte: u8| to_color32(hex_app.byte_color_table[usize::from(byte)]);
+    let table_ramp = || {
+        let samples = (0..=255).step_by(4).map(table).collect();
+        Ramp(samples, "0x00", "0xFF")
+    };
+
+    let (title, items) = match hex_app.color_mode {
+        ColorMode::Value if scheme == ColorScheme::Standard => (
+            "Value: byte bits as RGB".to_string(),
+            vec![
+                Swatch(table(0b1100_0000), "Bits 7-6: red"),
+                Swatch(table(0b0011_1000), "Bits 5-3: green"),
+                Swatch(table(0b0000_0111), "Bits 2-0: blue"),
+            ],
+        ),
+        ColorMode::Value => ("Value".to_string(), vec![table_ramp()]),
+        ColorMode::Semantic01 => (
+            "Semantic 01".to_string(),
+            vec![
+                Swatch(table(0x00), "0x00"),
+                Swatch(table(0xFF), "0xFF"),
+                Swatch(table(b'A'), "Printable ASCII"),
+                Swatch(table(0x80), "Other (brighter is higher)"),
+            ],
+        ),
+        ColorMode::Custom => {
+            let name = hex_app
+                .palettes
+                .selected_name()
+                .unwrap_or("no palette selected");
+            (format!("Custom: {name}"), vec![table_ramp()])
+        }
+        ColorMode::Diff => (
+            "Diff against the reference file".to_string(),
+            vec![
+                Swatch(to_color32(scheme.diff_color(Some(0), 1)), "Same"),
+                Ramp(
+                    ramp(|f| to_color32(scheme.diff_color(Some((f * 63.0) as usize + 1), 64))),
+                    "Few differ",
+                    "All differ",
+                ),
+                Swatch(
+                    to_color32(scheme.diff_color(None, 1)),
+                    "Past the end of a file",
+                ),
+            ],
+        ),
+        ColorMode::Variability => (
+            "Variability across all files".to_string(),
+            vec![
+                Swatch(
+                    to_color32(scheme.variability_color(0, 65)),
+                    "Same as the reference",
+                ),
+                Ramp(
+                    ramp(|f| to_color32(scheme.variability_color((f * 63.0) as u32 + 1, 65))),
+                    "Few files differ",
+                    "All differ",
+                ),
+            ],
+        ),
+        ColorMode::Printable => (
+            "Fraction of printable ASCII".to_string(),
+            vec![Ramp(
+                ramp(|f| to_color32(scheme.printable_color(f))),
+                "0%",
+                "100%",
+            )],
+        ),
+        ColorMode::Entropy => (
+            "Estimated entropy".to_string(),
+            vec![Ramp(
+                ramp(|f| to_color32(scheme.entropy_color(f))),
+                "Uniform",
+                "Random",
+            )],
+        ),
+        ColorMode::Range => (
+            "Range (max - min)".to_string(),
+            vec![Ramp(
+                ramp(|f| to_color32(scheme.range_color((f * 255.0) as u8))),
+                "0",
+                "255",
+            )],
+        ),
+        ColorMode::Padding => (
+            "Fraction of padding bytes".to_string(),
+            vec![
+                Swatch(to_color32(scheme.padding_color(1.0, 0.0)), "All 0x00"),
+                Swatch(to_color32(scheme.padding_color(0.0, 1.0)), "All 0xFF"),
+                Swatch(to_color32(scheme.padding_color(0.5, 0.5)), "Half each"),
+                Swatch(to_color32(scheme.padding_color(0.0, 0.0)), "Neither"),
+            ],
+        ),
+        ColorMode::Uniform => (
+            "Uniform blocks".to_string(),
+            vec![
+                Swatch(to_color32(scheme.uniform_color(Some(0x00))), "All 0x00"),
+                Swatch(to_color32(scheme.uniform_color(Some(0xFF))), "All 0xFF"),
+                Swatch(
+                    to_color32(scheme.uniform_color(Some(0x41))),
+                    "All another value",
+                ),
+                Swatch(to_color32(scheme.uniform_color(None)), "Mixed values"),
+            ],
+        ),
+    };
+
+    (title, items)
+}
+
+/// Draws the legend for the current color mode over the bottom left corner of `view_rect`
+/// (the main view's panel).
+pub fn legend(hex_app: &HexApp, ctx: &Context, view_rect: Rect) {
+    if !hex_app.legend {
+        return;
+    }
+    let (title, items) = legend_items(hex_app);
+
+    Area::new(Id::new("legend"))
+        .pivot(Align2::LEFT_BOTTOM)
+        .fixed_pos(view_rect.left_bottom() + vec2(8.0, -8.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(RichText::new(title).strong());
+                let by_value = matches!(
+                    hex_app.color_mode,
+                    ColorMode::Value | ColorMode::Semantic01 | ColorMode::Custom
+                );
+                if by_value && hex_app.color_averaging {
+                    ui.label(RichText::new("Blocks show the average color of their bytes").weak());
+                }
+                for item in items {
+                    legend_item(ui, item);
+                }
+            });
+        });
+}
+
+fn legend_item(ui: &mut Ui, item: LegendItem) {
+    let swatch_size = vec2(16.0, 12.0);
+
+    ui.horizontal(|ui| match item {
+        LegendItem::Swatch(color, text) => {
+            let (rect, _) = ui.allocate_exact_size(swatch_size, Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, color);
+            ui.painter()
+                .rect_stroke(rect, 2.0, ui.visuals().widgets.noninteractive.fg_stroke);
+            ui.label(text);
+        }
+        LegendItem::Ramp(colors, start, end) => {
+            ui.label(start);
+            let (rect, _) = ui.allocate_exact_size(vec2(128.0, swatch_size.y), Sense::hover());
+            let width = rect.width() / colors.len() as f32;
+            for (i, color) in colors.into_iter().enumerate() {
+                let min = rect.min + vec2(i as f32 * width, 0.0);
+                // (Overlap by a pixel so there are no gaps between samples.)
+                let sample = Rect::from_min_size(min, vec2(width + 1.0, rect.height()));
+                ui.painter().rect_filled(sample.intersect(rect), 0.0, color);
+            }
+            ui.label(end);
+        }
+    });
+}
@@ -0,0 +1,294 @@
+use std::path::{Path, PathBuf};
+
+use crate::hex_app::{generate_cache, HexApp};
+use range_blocks::{
+    cache_file::{CacheFileError, CacheKey},
+    color::Rgb,
+    distribution::{range_distribution, ByteDistribution, RangeBlockDistribution},
+    stats::{BlockStats, RangeBlockStats},
+    Cacheable, RangeBlockCache, RangeBlockColorSum,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A file loaded into `HexApp`, along with the range block caches generated from its contents.
+pub struct LoadedFile {
+    pub source_name: Option<String>,
+    pub data: Vec<u8>,
+    pub stats_cache: RangeBlockCache<BlockStats>,
+    pub distribution_cache: RangeBlockCache<ByteDistribution>,
+    /// Color sums of the bytes, colored by the color table with revision `palette_revision`
+    /// (for byte colors that `BlockStats` has no color sums for).
+    palette_cache: RangeBlockCache<(u64, u64, u64)>,
+    palette_revision: Option<u64>,
+}
+
+impl LoadedFile {
+    pub fn new(
+        source_name: Option<String>,
+        data: Vec<u8>,
+        cache_settings: &SavedCacheSettings,
+    ) -> Self {
+        let saved_caches = SavedCaches::new(&data, cache_settings);
+
+        Self {
+            source_name,
+            stats_cache: saved_caches
+                .load_or_generate(RangeBlockStats::CACHE_ID, &RangeBlockStats::new(&data)),
+            distribution_cache: saved_caches.load_or_generate(
+                RangeBlockDistribution::CACHE_ID,
+                &RangeBlockDistribution::new(&data),
+            ),
+            palette_cache: RangeBlockCache::new(),
+            palette_revision: None,
+            data,
+        }
+    }
+
+    /// The `BlockStats` of a range block: cached, or calculated on demand.
+    pub fn stats(&self, index: u64, count: u64) -> BlockStats {
+        self.stats_cache
+            .get(index, count)
+            .unwrap_or_else(|| RangeBlockStats::new(&self.data).value(index, count))
+    }
+
+    /// Regenerates the palette color sum cache if the color table `table` with `revision`
+    /// isn't the one it was generated for. Returns whether it was regenerated.
+    pub fn update_palette_cache(&mut self, table: &[Rgb; 256], revision: u64) -> bool {
+        if self.palette_revision == Some(revision) {
+            return false;
+        }
+        self.palette_cache = generate_cache(&self.palette_color_sum(table), self.data.len());
+        self.palette_revision = Some(revision);
+        true
+    }
+
+    /// The average color of a range block's bytes, colored by `table`.
+    /// Cached range blocks use the colors of the last `update_palette_cache` table,
+    /// which may be older than `table` while it's being edited.
+    pub fn palette_average_color(&self, table: &[Rgb; 256], index: u64, count: u64) -> Rgb {
+        let (r, g, b) = self
+            .palette_cache
+            .get(index, count)
+            .unwrap_or_else(|| self.palette_color_sum(table).value(index, count));
+        // (The final range block may be incomplete.)
+        let len = (self.data.len() as u64)
+            .saturating_sub(index)
+            .min(count)
+            .max(1);
+        Rgb::new((r / len) as u8, (g / len) as u8, (b / len) as u8)
+    }
+
+    fn palette_color_sum<'a>(&'a self, table: &[Rgb; 256]) -> RangeBlockColorSum<'a, 'static> {
+        let table = *table;
+        RangeBlockColorSum::new(&self.data, move |byte| table[usize::from(byte)].to_u64s())
+    }
+
+    /// The `ByteDistribution` of `len` bytes starting at `index` (or fewer, at the end of the file).
+    pub fn distribution(&self, index: u64, len: u64) -> ByteDistribution {
+        range_distribution(
+            &self.data,
+            &self.distribution_cache,
+            index,
+            len,
+            HexApp::SUB_BLOCK_SQRT,
+        )
+    }
+
+    /// UI label for the file at `file_index` in `HexApp::files`.
+    pub fn label(&self, file_index: usize) -> String {
+        match &self.source_name {
+            Some(name) => format!("File{file_index}: {name}"),
+            None => format!("File{file_index}"),
+        }
+    }
+}
+
+/// Settings of `SavedCaches` (saved with the app settings).
+#[derive(Serialize, Deserialize)]
+pub struct SavedCacheSettings {
+    pub enabled: bool,
+    /// Once the saved caches take up more than this, the least recently used are deleted.
+    pub max_size_mib: u64,
+    /// The result of the last `clear`.
+    #[serde(skip)]
+    pub status: String,
+}
+
+impl Default for SavedCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_size_mib: 1024,
+            status: String::new(),
+        }
+    }
+}
+
+impl SavedCacheSettings {
+    /// The key that the settings are saved under in eframe's storage.
+    pub const STORAGE_KEY: &'static str = "saved_caches";
+
+    /// Deletes all saved caches. Returns a status message.
+    pub fn clear(&self) -> String {
+        let Some(dir) = cache_dir() else {
+            return "No cache directory".to_string();
+        };
+        match std::fs::remove_dir_all(&dir) {
+            Ok(()) => format!("Deleted {dir:?}"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => "No saved caches".to_string(),
+            Err(e) => format!("Deleting {dir:?} failed: {e}"),
+        }
+    }
+}
+
+/// The directory that `SavedCaches` are saved in.
+fn cache_dir() -> Option<PathBuf> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        eframe::storage_dir(HexApp::APP_NAME).map(|dir| dir.join("range_block_caches"))
+    }
+
+    // No file system on the web.
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+/// Saves the range block caches of large files in the user's data directory,
+/// so they can be loaded instead of regenerated the next time the file is opened.
+struct SavedCaches {
+    /// The cache directory and the file's content hash, if the caches should be saved.
+    dir: Option<(PathBuf, [u8; 32])>,
+    data_len: usize,
+    /// See `SavedCacheSettings::max_size_mib`.
+    max_size: u64,
+}
+
+impl SavedCaches {
+    /// Caches of files smaller than this are quick enough to regenerate.
+    const MIN_DATA_LEN: usize = 1 << 24;
+    const EXTENSION: &'static str = "rbcache";
+
+    fn new(data: &[u8], settings: &SavedCacheSettings) -> Self {
+        let dir = cache_dir()
+            .filter(|_| settings.enabled && data.len() >= Self::MIN_DATA_LEN)
+            .map(|dir| (dir, CacheKey::content_hash(data)));
+
+        Self {
+            dir,
+            data_len: data.len(),
+            max_size: settings.max_size_mib.saturating_mul(1 << 20),
+        }
+    }
+
+    /// Loads the saved cache for `cacheable`, or generates (and saves) it
+    /// if it is missing, corrupt, or doesn't match this file.
+    fn load_or_generate<T>(
+        &self,
+        cacheable_id: &str,
+        cacheable: &(impl Cacheable<T> + Sync),
+    ) -> RangeBlockCache<T>
+    where
+        T: Clone + Send + Sync + Serialize + DeserializeOwned,
+    {
+        let Some((dir, content_hash)) = &self.dir else {
+            return generate_cache(cacheable, self.data_len);
+        };
+
+        let key = CacheKey::new(
+            *content_hash,
+            self.data_len,
+            HexApp::SUB_BLOCK_SQRT,
+            cacheable_id,
+        );
+        let path = dir.join(key.file_name());
+
+        match load(&key, &path) {
+            Ok(cache) => {
+                log::info!("loaded {cacheable_id} cache from {path:?}");
+                // (Marks the cache as recently used, for `prune`.)
+                let touched = std::fs::File::options()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+                if let Err(e) = touched {
+                    log::warn!("failed to touch {path:?}: {e}");
+                }
+                return cache;
+            }
+            Err(CacheFileError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("regenerating {cacheable_id} cache {path:?}: {e}"),
+        }
+
+        let cache = generate_cache(cacheable, self.data_len);
+        if let Err(e) = save(&cache, &key, &path) {
+            log::warn!("failed to save {cacheable_id} cache {path:?}: {e}");
+        }
+        if let Err(e) = self.prune(dir, &path) {
+            log::warn!("failed to prune saved caches in {dir:?}: {e}");
+        }
+
+        cache
+    }
+
+    /// Deletes the least recently used caches in `dir` (other than `keep`) until they take up
+    /// no more than `max_size`.
+    fn prune(&self, dir: &Path, keep: &Path) -> std::io::Result<()> {
+        let mut caches = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == Self::EXTENSION)
+            {
+                let metadata = std::fs::metadata(&path)?;
+                caches.push((metadata.modified()?, metadata.len(), path));
+            }
+        }
+
+        let mut total_size: u64 = caches.iter().map(|(_, len, _)| len).sum();
+        caches.sort();
+        for (_, len, path) in caches {
+            if total_size <= self.max_size {
+                break;
+            }
+            if path != keep {
+                log::info!("deleting least recently used cache {path:?}");
+                std::fs::remove_file(&path)?;
+                total_size -= len;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn load<T>(key: &CacheKey, path: &Path) -> Result<RangeBlockCache<T>, CacheFileError>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    let file = std::fs::File::open(path)?;
+    RangeBlockCache::read(key, std::io::BufReader::new(file))
+}
+
+/// Writes to a temporary file first, so a partially written cache never replaces a good one.
+fn save<T>(cache: &RangeBlockCache<T>, key: &CacheKey, path: &Path) -> Result<(), CacheFileError>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    let result = std::fs::File::create(&temp_path)
+        .map_err(CacheFileError::from)
+        .and_then(|file| cache.write(key, std::io::BufWriter::new(file)))
+        .and_then(|()| Ok(std::fs::rename(&temp_path, path)?));
+    if result.is_err() {
+        // (Ignoring errors: the file may not have been created.)
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result
+}
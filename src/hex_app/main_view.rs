@@ -1,39 +1,86 @@
 use std::collections::HashSet;
 
-use crate::hex_app::{byte_text, ColorMode, HexApp, WhichFile};
-use crate::range_blocks::{
-    max_recursion_level, range_block_corners, Cacheable, CellCoords,
-    CompleteLargestRangeBlockIterator, RangeBlockColorSum, RangeBlockDiff, RangeBlockIterator,
-    RangeBlockSum,
+use crate::hex_app::{byte_text, navigation, ColorMode, HexApp, MainViewLayout};
+use crate::utilities::{diff_color, entropy_color, printable_color, text_color, to_color32};
+use crate::utilities::{padding_color, range_color, uniform_color, variability_color};
+use egui::epaint::{RectShape, TessellationOptions, Tessellator, Vertex};
+use egui::{
+    Align2, Color32, Context, FontId, Mesh, Painter, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2,
 };
-use crate::range_border::{LoopPairIter, LoopsIter, RangeBorder};
-use crate::utilities::{byte_color, contrast, diff_color};
-use crate::utilities::{byte_color_rgb, semantic01_color, semantic01_color_rgb};
-use egui::{Align2, Color32, Context, FontId, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+use range_blocks::range_border::{LoopPairIter, LoopsIter, RangeBorder};
+use range_blocks::RangeBlockVariability;
+use range_blocks::{
+    color::ColorScheme, max_recursion_level, range_block_corners, Cacheable, CellCoords,
+    CompleteLargestRangeBlockIterator, RangeBlockDiff, RangeBlockIterator,
+};
+
+/// The fraction of each recursion level's zoom range (in log scale) over which
+/// the next coarser level fades in.
+const LEVEL_FADE: f32 = 0.25;
+/// Range block sizes (in points) over which block address text fades in.
+const BLOCK_TEXT_FADE: (f32, f32) = (80.0, 120.0);
+/// Cell sizes (in points) over which cell text fades in.
+const CELL_TEXT_FADE: (f32, f32) = (6.0, 12.0);
 
 /// Draws the main view containing range blocks with a mouse-controlled pan+zoom interface.
+/// Depending on `HexApp::main_view_layout`, this shows the active file alone, or
+/// the active and reference files in two linked views that share zoom, pan, and selection.
 pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
     hex_app.selected_range_block = None; // Reset selected range block (should this be done some other way?)
 
-    let (response, painter) =
-        ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
-
-    // Mousewheel zoom
-    if ui.ui_contains_pointer() {
-        let scroll_delta = ui.input(|i| i.smooth_scroll_delta);
-
-        if scroll_delta.y != 0.0 {
-            let prev_zoom = hex_app.zoom;
-            hex_app.zoom *= 1.0 + scroll_delta.y * 0.005;
-
-            // Clamp zoom to intended range to prevent smooth scroll "bounce" effect.
-            hex_app.zoom = hex_app.zoom.clamp(HexApp::MIN_ZOOM, HexApp::MAX_ZOOM);
-
-            // Add a pan offset to keep the zoom effect centered on the mouse cursor.
-            if let Some(cursor_pos) = response.hover_pos() {
-                let screen_center_to_cursor = cursor_pos - painter.clip_rect().center();
-                let pan_center_to_cursor = screen_center_to_cursor - hex_app.pan;
-                hex_app.pan -= pan_center_to_cursor * ((hex_app.zoom / prev_zoom) - 1.0);
+    let rect = ui.available_rect_before_wrap();
+    let rects = match hex_app.main_view_layout {
+        MainViewLayout::Single => vec![rect],
+        MainViewLayout::SideBySide => {
+            let (left, right) = rect.split_left_right_at_fraction(0.5);
+            vec![
+                left.shrink2(Vec2::new(2.0, 0.0)),
+                right.shrink2(Vec2::new(2.0, 0.0)),
+            ]
+        }
+        MainViewLayout::Stacked => {
+            let (top, bottom) = rect.split_top_bottom_at_fraction(0.5);
+            vec![
+                top.shrink2(Vec2::new(0.0, 2.0)),
+                bottom.shrink2(Vec2::new(0.0, 2.0)),
+            ]
+        }
+    };
+    hex_app.main_view_size = rects[0].size();
+    let file_pairs = [
+        (hex_app.active_file, hex_app.reference_file),
+        (hex_app.reference_file, hex_app.active_file),
+    ];
+
+    let views: Vec<(Response, Painter)> = rects
+        .into_iter()
+        .map(|rect| {
+            (
+                ui.allocate_rect(rect, Sense::click_and_drag()),
+                ui.painter_at(rect),
+            )
+        })
+        .collect();
+
+    for (response, painter) in &views {
+        // Mousewheel zoom
+        if response.contains_pointer() {
+            let scroll_delta = ui.input(|i| i.smooth_scroll_delta);
+
+            if scroll_delta.y != 0.0 {
+                hex_app.view_transition = None;
+                let prev_zoom = hex_app.zoom;
+                hex_app.zoom *= 1.0 + scroll_delta.y * 0.005;
+
+                // Clamp zoom to intended range to prevent smooth scroll "bounce" effect.
+                hex_app.zoom = hex_app.zoom.clamp(HexApp::MIN_ZOOM, HexApp::MAX_ZOOM);
+
+                // Add a pan offset to keep the zoom effect centered on the mouse cursor.
+                if let Some(cursor_pos) = response.hover_pos() {
+                    let screen_center_to_cursor = cursor_pos - painter.clip_rect().center();
+                    let pan_center_to_cursor = screen_center_to_cursor - hex_app.pan;
+                    hex_app.pan -= pan_center_to_cursor * ((hex_app.zoom / prev_zoom) - 1.0);
+                }
             }
         }
     }
@@ -43,16 +90,237 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
     let dt = (current_time - hex_app.last_update_time) as f32;
     hex_app.last_update_time = current_time;
 
-    if response.dragged() {
+    if let Some((response, _)) = views.iter().find(|(response, _)| response.dragged()) {
+        hex_app.view_transition = None;
         hex_app.pan_velocity = response.drag_delta() / dt;
+        hex_app.pan += response.drag_delta();
     } else {
         hex_app.pan += hex_app.pan_velocity * dt;
         hex_app.pan_velocity *= HexApp::FRICTION.powf(dt * 60.0);
     }
+    if views.iter().any(|(response, _)| response.clicked()) {
+        hex_app.main_view_focus = true;
+    }
+    navigation::update_view_transition(hex_app, ui.ctx(), current_time);
+
+    *hex_app.rect_draw_count.borrow_mut() = 0;
+
+    for (view_index, ((response, painter), (file_index, other_file_index))) in
+        views.iter().zip(file_pairs).enumerate()
+    {
+        range_block_view(
+            hex_app,
+            response,
+            painter,
+            view_index,
+            file_index,
+            other_file_index,
+        );
+    }
+}
 
-    hex_app.pan += response.drag_delta();
+/// The (fractional) recursion level whose range blocks suit a view `view_width` points wide at `zoom`.
+pub fn recursion_level(view_width: f32, zoom: f32) -> f32 {
+    let cell_width = view_width / zoom;
+
+    cell_width.log(HexApp::SUB_BLOCK_SQRT as f32) - 1.0
+}
+
+/// The fraction of the view's size that a `BlockMesh` covers beyond each side of the view,
+/// so short pans don't need a new mesh.
+const BLOCK_MESH_MARGIN: f32 = 0.125;
+/// The range of zoom changes (as `zoom / BlockMesh::zoom`) over which a `BlockMesh` is
+/// scaled instead of tessellated again, while the zoom is changing.
+const BLOCK_MESH_SCALES: std::ops::RangeInclusive<f32> = 0.5..=2.0;
+
+/// The range block fills of one view, tessellated into a single mesh.
+pub struct BlockMesh {
+    key: BlockMeshKey,
+    /// The zoom that `mesh` was tessellated at.
+    zoom: f32,
+    /// The zoom that `mesh` was last drawn at.
+    drawn_zoom: f32,
+    /// Relative to the position of cell (0, 0), at `zoom`.
+    mesh: Mesh,
+    /// The shapes of each range block in `mesh`, in order.
+    parts: Vec<BlockMeshPart>,
+    /// The region (relative to the position of cell (0, 0), at `zoom`) whose range blocks
+    /// are in `mesh`.
+    coverage: Rect,
+}
 
-    *hex_app.rect_draw_count.borrow_mut() = 1;
+/// The shapes of one range block in a `BlockMesh`: the vertices and indices from these
+/// starts up to the next part's.
+struct BlockMeshPart {
+    rect: Rect,
+    vertices: usize,
+    indices: usize,
+}
+
+impl BlockMesh {
+    /// The parts of `mesh` that intersect `view_rect` (in `mesh` coordinates), scaled by
+    /// `scale`, translated by `origin`, and faded by `alpha`.
+    fn visible_mesh(&self, view_rect: Rect, scale: f32, origin: Vec2, alpha: f32) -> Mesh {
+        let mut mesh = Mesh::default();
+        let ends = self
+            .parts
+            .iter()
+            .skip(1)
+            .map(|part| (part.vertices, part.indices))
+            .chain([(self.mesh.vertices.len(), self.mesh.indices.len())]);
+
+        for (part, (vertices_end, indices_end)) in self.parts.iter().zip(ends) {
+            if !part.rect.intersects(view_rect) {
+                continue;
+            }
+            let first_vertex = mesh.vertices.len() as u32;
+            mesh.vertices
+                .extend(
+                    self.mesh.vertices[part.vertices..vertices_end]
+                        .iter()
+                        .map(|vertex| Vertex {
+                            pos: (vertex.pos.to_vec2() * scale + origin).to_pos2(),
+                            color: vertex.color.gamma_multiply(alpha),
+                            ..*vertex
+                        }),
+                );
+            mesh.indices.extend(
+                self.mesh.indices[part.indices..indices_end]
+                    .iter()
+                    .map(|&index| index - part.vertices as u32 + first_vertex),
+            );
+        }
+
+        mesh
+    }
+}
+
+/// Everything a `BlockMesh` depends on, other than the pan and the loaded files.
+#[derive(Debug, PartialEq)]
+pub struct BlockMeshKey {
+    file_index: usize,
+    other_file_index: usize,
+    color_mode: ColorMode,
+    color_averaging: bool,
+    split_diff_overlay: bool,
+    final_incomplete_block: bool,
+    pixels_per_point: f32,
+    recursion_level: u32,
+    diff_cache_files: Option<(usize, usize)>,
+    variability_cache_files: Option<(usize, usize)>,
+    color_scheme: ColorScheme,
+    byte_colors_revision: u64,
+}
+
+impl BlockMeshKey {
+    /// The key for the range blocks of the (file, other file) at `file_indices`,
+    /// drawn at `recursion_level`.
+    pub fn new(
+        hex_app: &HexApp,
+        painter: &Painter,
+        (file_index, other_file_index): (usize, usize),
+        recursion_level: u32,
+        split_diff_overlay: bool,
+    ) -> Self {
+        Self {
+            file_index,
+            other_file_index,
+            color_mode: hex_app.color_mode,
+            color_averaging: hex_app.color_averaging,
+            split_diff_overlay,
+            final_incomplete_block: hex_app.ui_config.final_incomplete_block,
+            pixels_per_point: painter.ctx().pixels_per_point(),
+            recursion_level,
+            diff_cache_files: hex_app.diff_cache_files,
+            variability_cache_files: hex_app.variability_cache_files,
+            color_scheme: hex_app.color_scheme,
+            byte_colors_revision: hex_app.byte_colors_revision,
+        }
+    }
+}
+
+/// Draws the range block fills described by `key` at `zoom` with cell (0, 0) at `origin`,
+/// faded by `alpha`.
+/// `cached` is reused if it has the same key and covers `painter`'s clip rect (scaled, while
+/// the zoom is changing): returns the `BlockMesh` to cache for the next frame.
+pub fn draw_block_mesh(
+    hex_app: &HexApp,
+    painter: &Painter,
+    cached: Option<BlockMesh>,
+    key: BlockMeshKey,
+    zoom: f32,
+    origin: Vec2,
+    alpha: f32,
+) -> BlockMesh {
+    let view_rect = painter.clip_rect().translate(-origin);
+    // `view_rect` in the coordinates of a mesh tessellated at `mesh_zoom`.
+    let mesh_view_rect = |mesh_zoom: f32| {
+        let scale = mesh_zoom / zoom;
+        Rect::from_min_max(
+            (view_rect.min.to_vec2() * scale).to_pos2(),
+            (view_rect.max.to_vec2() * scale).to_pos2(),
+        )
+    };
+
+    let mut block_mesh = cached
+        .filter(|block_mesh| {
+            let scale = zoom / block_mesh.zoom;
+            // (Tessellated again at the exact zoom once the zoom stops changing.)
+            let zooming = zoom != block_mesh.drawn_zoom && BLOCK_MESH_SCALES.contains(&scale);
+            block_mesh.key == key
+                && (scale == 1.0 || zooming)
+                && block_mesh
+                    .coverage
+                    .contains_rect(mesh_view_rect(block_mesh.zoom))
+        })
+        .unwrap_or_else(|| {
+            let coverage = view_rect.expand2(view_rect.size() * BLOCK_MESH_MARGIN);
+            build_block_mesh(hex_app, painter, key, zoom, coverage)
+        });
+    block_mesh.drawn_zoom = zoom;
+
+    let mesh = block_mesh.visible_mesh(
+        mesh_view_rect(block_mesh.zoom),
+        zoom / block_mesh.zoom,
+        origin,
+        alpha,
+    );
+    *hex_app.rect_draw_count.borrow_mut() += 1;
+    painter.add(mesh);
+
+    block_mesh
+}
+
+/// A shape drawn over a range block in a `BlockMesh`.
+#[derive(Debug, Clone, Copy)]
+enum BlockShape {
+    Fill(Color32),
+    Outline(Color32),
+}
+
+impl BlockShape {
+    fn rect_shape(self, rect: Rect) -> RectShape {
+        match self {
+            Self::Fill(color) => RectShape::filled(rect, 10.0, color),
+            Self::Outline(color) => {
+                RectShape::stroke(rect.shrink(1.0), 10.0, Stroke::new(2.0, color))
+            }
+        }
+    }
+}
+
+/// Draws the range blocks of the file at `file_index` in `painter`'s clip rect,
+/// comparing it against the file at `other_file_index` where needed.
+/// `view_index` identifies the view's cached `BlockMesh`es.
+fn range_block_view(
+    hex_app: &mut HexApp,
+    response: &Response,
+    painter: &Painter,
+    view_index: usize,
+    file_index: usize,
+    other_file_index: usize,
+) {
+    *hex_app.rect_draw_count.borrow_mut() += 1;
     painter.rect_filled(painter.clip_rect(), 10.0, Color32::GRAY);
 
     // Local drawing utility functions:
@@ -113,8 +381,9 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
                 color,
             );
         };
+    // Returns the text's bounding rect.
     let draw_centered_text =
-        |top_left: CellCoords, bottom_right: CellCoords, color: Color32, text: &str| {
+        |top_left: CellCoords, bottom_right: CellCoords, color: Color32, text: &str| -> Rect {
             let rect = Rect::from_two_pos(painter_coords(top_left), painter_coords(bottom_right));
             painter.text(
                 rect.center(),
@@ -122,32 +391,14 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
                 text,
                 FontId::default(),
                 color,
-            );
+            )
         };
 
-    let data = match hex_app.active_file {
-        WhichFile::File0 => &hex_app.pattern0,
-        WhichFile::File1 => &hex_app.pattern1,
-    };
-    let other_data = match hex_app.active_file {
-        WhichFile::File0 => &hex_app.pattern1,
-        WhichFile::File1 => &hex_app.pattern0,
-    };
-
-    let data_cache = match hex_app.active_file {
-        WhichFile::File0 => &hex_app.cache0,
-        WhichFile::File1 => &hex_app.cache1,
-    };
+    let data = hex_app.files.get(file_index).map(|file| &file.data);
+    let other_data = hex_app.files.get(other_file_index).map(|file| &file.data);
 
-    let color_cache_value = match hex_app.active_file {
-        WhichFile::File0 => &hex_app.color_cache_value0,
-        WhichFile::File1 => &hex_app.color_cache_value1,
-    };
-
-    let color_cache_semantic01 = match hex_app.active_file {
-        WhichFile::File0 => &hex_app.color_cache_semantic01_0,
-        WhichFile::File1 => &hex_app.color_cache_semantic01_1,
-    };
+    // A range block whose address label was clicked.
+    let mut zoom_to_block = None;
 
     if let Some(data) = data {
         let data_len: u64 = data.len().try_into().expect("data.len() should fit in u64");
@@ -155,11 +406,24 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
         let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
 
         // Automatically choose a range block recursion level based on the current zoom level.
-        let rendered_recursion_level = std::cmp::min(max_recursion_level, {
-            let cell_width = painter.clip_rect().width() / hex_app.zoom;
-
-            cell_width.log(sub_block_sqrt as f32) as u32 - 1
-        });
+        // (`recursion_level`'s fractional part drives the cross-fade between levels.)
+        let recursion_level = recursion_level(painter.clip_rect().width(), hex_app.zoom);
+        let rendered_recursion_level =
+            std::cmp::min(max_recursion_level, recursion_level.max(0.0) as u32);
+
+        // Fade in the next coarser recursion level near the end of this level's zoom range,
+        // so colors and text don't pop in when `rendered_recursion_level` changes.
+        let coarse_alpha = if rendered_recursion_level < max_recursion_level {
+            let fraction = recursion_level - rendered_recursion_level as f32;
+            ((fraction - (1.0 - LEVEL_FADE)) / LEVEL_FADE).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        // (recursion level, alpha) of each rendered layer, finest first.
+        let layers = [
+            (rendered_recursion_level, 1.0),
+            (rendered_recursion_level + 1, coarse_alpha),
+        ];
 
         hex_app.dbg_notes = format!(
             "max_recursion_level: {}, rendered_recursion_level: {}",
@@ -215,108 +479,156 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
             );
         }
 
-        // Draw visible range blocks + related effects.
-        for (index, count) in visible_range_blocks(rendered_recursion_level) {
-            let diff_bytes = if hex_app.color_mode == ColorMode::Diff {
-                if let Some(other_data) = other_data {
-                    hex_app.diff_cache.get(index, count).unwrap_or_else(|| {
-                        RangeBlockDiff::new(data, other_data).value(index, count)
-                    })
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+        // Range block fills (and split diff overlays) of each layer are tessellated into one mesh,
+        // which is only translated while the pan is the only thing that changes.
+        let origin = painter_coords(CellCoords { x: 0, y: 0 }).to_vec2();
+        let split_diff_overlay =
+            hex_app.main_view_layout != MainViewLayout::Single && hex_app.split_diff_overlay;
 
-            let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
-            let rect = Rect::from_two_pos(painter_coords(top_left), painter_coords(bottom_right));
+        for (layer, &(recursion_level, alpha)) in layers.iter().enumerate() {
+            let cached = hex_app.block_meshes[view_index][layer].take();
+            if alpha == 0.0 {
+                continue;
+            }
+
+            let key = BlockMeshKey::new(
+                hex_app,
+                painter,
+                (file_index, other_file_index),
+                recursion_level,
+                split_diff_overlay,
+            );
+            hex_app.block_meshes[view_index][layer] = Some(draw_block_mesh(
+                hex_app,
+                painter,
+                cached,
+                key,
+                hex_app.zoom,
+                origin,
+                alpha,
+            ));
+        }
 
-            let fill_color = if response.clicked()
-                && response
-                    .interact_pointer_pos()
-                    .map(|pos| rect.contains(pos))
-                    .unwrap_or(false)
-            {
-                hex_app.selected_index = Some(index.try_into().expect("temp fix"));
-                Color32::WHITE
+        // Draw per-frame effects of visible range blocks.
+        let block_colors = BlockColors::new(hex_app);
+        // A range block that was clicked (selected after drawing).
+        let mut clicked_index = None;
+        for &(recursion_level, alpha) in &layers {
+            if alpha == 0.0 {
+                continue;
+            }
+            let is_rendered_level = recursion_level == rendered_recursion_level;
+            // The finer layer's text fades out as the coarser layer's fades in.
+            let text_alpha = if is_rendered_level {
+                1.0 - coarse_alpha
             } else {
-                match hex_app.color_mode {
-                    x @ (ColorMode::Value | ColorMode::Semantic01) => {
-                        if hex_app.color_averaging {
-                            let (r, g, b) = if x == ColorMode::Value {
-                                color_cache_value.get(index, count).unwrap_or_else(|| {
-                                    RangeBlockColorSum::new(data, byte_color_rgb)
-                                        .value(index, count)
-                                })
-                            } else {
-                                color_cache_semantic01.get(index, count).unwrap_or_else(|| {
-                                    RangeBlockColorSum::new(data, semantic01_color_rgb)
-                                        .value(index, count)
-                                })
-                            };
-                            Color32::from_rgb(
-                                (r as f32 / count as f32) as u8,
-                                (g as f32 / count as f32) as u8,
-                                (b as f32 / count as f32) as u8,
-                            )
-                        } else {
-                            let sum = data_cache
-                                .get(index, count)
-                                .unwrap_or_else(|| RangeBlockSum::new(data).value(index, count));
-                            let average = sum as f32 / count as f32;
-                            if x == ColorMode::Semantic01 {
-                                semantic01_color(average as u8)
-                            } else {
-                                byte_color(average as u8)
-                            }
-                        }
+                coarse_alpha
+            };
+
+            for (index, count) in visible_range_blocks(recursion_level) {
+                let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+                let rect =
+                    Rect::from_two_pos(painter_coords(top_left), painter_coords(bottom_right));
+
+                let clicked = is_rendered_level
+                    && response.clicked()
+                    && response
+                        .interact_pointer_pos()
+                        .map(|pos| rect.contains(pos))
+                        .unwrap_or(false);
+                if clicked {
+                    clicked_index = Some(index.try_into().expect("temp fix"));
+                    draw_rounded_filled_box(top_left, bottom_right, Color32::WHITE);
+                }
+                // (The fill itself is in the `BlockMesh`: its color is only needed for text.)
+                let fill_color = || {
+                    if clicked {
+                        Color32::WHITE
+                    } else {
+                        block_colors.color(file_index, other_file_index, index, count)
                     }
-                    ColorMode::Diff => diff_color(diff_bytes, count),
+                };
+
+                if hex_app.ui_config.final_incomplete_block && index + count > data_len {
+                    // Final incomplete range block
+                    if let Some(count) = data_len.checked_sub(index) {
+                        draw_range_border(
+                            selection_range_blocks(index, count),
+                            sub_block_sqrt,
+                            |start, corner, end| {
+                                draw_rounded_corner(
+                                    start,
+                                    corner,
+                                    end,
+                                    fill_color().gamma_multiply(alpha),
+                                );
+                            },
+                        );
+                    } else {
+                        // This should be impossible.
+                        log::error!("index > data_len");
+                    }
+                    continue;
                 }
-            };
 
-            if hex_app.ui_config.final_incomplete_block && index + count > data_len {
-                // Final incomplete range block
-                if let Some(count) = data_len.checked_sub(index) {
-                    draw_range_boxes(
-                        selection_range_blocks(index, count),
-                        sub_block_sqrt,
-                        |top_left, bottom_right| {
-                            draw_rounded_filled_box(top_left, bottom_right, fill_color);
-                        },
-                    );
-                    draw_range_border(
-                        selection_range_blocks(index, count),
-                        sub_block_sqrt,
-                        |start, corner, end| {
-                            draw_rounded_corner(start, corner, end, fill_color);
-                        },
-                    );
+                if recursion_level == 0 {
+                    let alpha = text_alpha * fade_in(rect.width(), CELL_TEXT_FADE);
+                    if hex_app.ui_config.cell_text && alpha > 0.0 {
+                        let byte: u8 = data[usize::try_from(index).expect("temp fix")];
+                        let display_text = byte_text(byte, hex_app.cell_view_mode);
+                        let color = text_color(fill_color()).gamma_multiply(alpha);
+                        draw_cell_text(top_left, bottom_right, color, &display_text);
+                    }
                 } else {
-                    // This should be impossible.
-                    log::error!("index > data_len");
+                    let alpha = text_alpha * fade_in(rect.width(), BLOCK_TEXT_FADE);
+                    if hex_app.ui_config.block_address_text && alpha > 0.0 {
+                        let diff_text = if hex_app.color_mode != ColorMode::Diff {
+                            String::new()
+                        } else if let Some(diff_bytes) =
+                            diff_bytes(hex_app, file_index, other_file_index, index, count)
+                        {
+                            format!("\n{}", diff_bytes as f32 / count as f32)
+                        } else {
+                            String::new()
+                        };
+                        let text = format!("0x{:08X}\n{} bytes\n{}", index, count, diff_text);
+                        let color = text_color(fill_color()).gamma_multiply(alpha);
+                        let text_rect = draw_centered_text(top_left, bottom_right, color, &text);
+
+                        // Clicking a (mostly faded in) address label zooms into its block.
+                        if alpha > 0.5
+                            && response.clicked()
+                            && response
+                                .interact_pointer_pos()
+                                .is_some_and(|pos| text_rect.contains(pos))
+                        {
+                            zoom_to_block = Some((index, count));
+                        }
+                    }
                 }
-                continue;
             }
+        }
 
-            draw_rounded_filled_box(top_left, bottom_right, fill_color);
-
-            let diff_text = if let Some(diff_bytes) = diff_bytes {
-                format!("\n{}", diff_bytes as f32 / count as f32)
-            } else {
-                String::new()
-            };
+        if clicked_index.is_some() {
+            hex_app.selected_index = clicked_index;
+        }
 
-            if rendered_recursion_level == 0 {
-                if hex_app.ui_config.cell_text {
-                    let byte: u8 = data[usize::try_from(index).expect("temp fix")];
-                    let display_text = byte_text(byte, hex_app.cell_view_mode);
-                    draw_cell_text(top_left, bottom_right, contrast(fill_color), &display_text);
+        // Highlight strings listed in the strings window, more strongly where they cover more of a block.
+        if hex_app.strings_window && hex_app.strings.highlighted_file() == Some(file_index) {
+            for (index, count) in visible_range_blocks(rendered_recursion_level) {
+                let covered = hex_app.strings.covered_len(index, count);
+                if covered == 0 {
+                    continue;
                 }
-            } else if hex_app.ui_config.block_address_text {
-                let text = format!("0x{:08X}\n{} bytes\n{}", index, count, diff_text);
-                draw_centered_text(top_left, bottom_right, contrast(fill_color), &text);
+                let alpha = 48 + (144 * covered / count) as u8;
+                let color = Color32::from_rgba_unmultiplied(255, 255, 0, alpha);
+                draw_range_boxes(
+                    selection_range_blocks(index, count.min(data_len - index)),
+                    sub_block_sqrt,
+                    |top_left, bottom_right| {
+                        draw_rounded_filled_box(top_left, bottom_right, color);
+                    },
+                );
             }
         }
 
@@ -409,11 +721,213 @@ pub fn main_view(hex_app: &mut HexApp, _ctx: &Context, ui: &mut Ui) {
         if let Some(cursor_pos) = response.hover_pos() {
             let rect = Rect::from_min_size(cursor_pos, Vec2::splat(10.0));
             *hex_app.rect_draw_count.borrow_mut() += 1;
-            painter.rect_filled(rect, 0.0, byte_color(0));
+            painter.rect_filled(rect, 0.0, Color32::BLACK);
+        }
+    }
+
+    if let Some((index, count)) = zoom_to_block {
+        navigation::zoom_to_range(hex_app, index, count);
+    }
+}
+
+/// Tessellates the fills (and split diff overlays) of the range blocks in `coverage`
+/// (relative to the position of cell (0, 0)) at `key.recursion_level` and `zoom`.
+fn build_block_mesh(
+    hex_app: &HexApp,
+    painter: &Painter,
+    key: BlockMeshKey,
+    zoom: f32,
+    coverage: Rect,
+) -> BlockMesh {
+    let data_len = hex_app
+        .files
+        .get(key.file_index)
+        .map_or(0, |file| file.data.len() as u64);
+    let sub_block_sqrt = HexApp::SUB_BLOCK_SQRT;
+    let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+    let mut tessellator = Tessellator::new(
+        key.pixels_per_point,
+        TessellationOptions::default(),
+        painter.ctx().fonts(|fonts| fonts.font_image_size()),
+        Vec::new(),
+    );
+    let mut mesh = Mesh::default();
+    let mut parts = Vec::new();
+
+    let mesh_rect = |top_left: CellCoords, bottom_right: CellCoords| {
+        let pos = |point: CellCoords| Pos2::new(point.x as f32, point.y as f32) * zoom;
+        Rect::from_two_pos(pos(top_left), pos(bottom_right))
+    };
+    let mut add_rect =
+        |mesh: &mut Mesh, top_left: CellCoords, bottom_right: CellCoords, shape: BlockShape| {
+            let rect = shape.rect_shape(mesh_rect(top_left, bottom_right));
+            tessellator.tessellate_rect(&rect, mesh);
+        };
+    let is_covered = |index: u64, count: u64| {
+        let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+        coverage.intersects(mesh_rect(top_left, bottom_right))
+    };
+    let block_colors = BlockColors::new(hex_app);
+
+    for (index, count) in RangeBlockIterator::new(
+        0,
+        data_len,
+        key.recursion_level,
+        max_recursion_level,
+        sub_block_sqrt,
+        is_covered,
+    ) {
+        let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+        parts.push(BlockMeshPart {
+            rect: mesh_rect(top_left, bottom_right),
+            vertices: mesh.vertices.len(),
+            indices: mesh.indices.len(),
+        });
+        let fill = BlockShape::Fill(block_colors.color(
+            key.file_index,
+            key.other_file_index,
+            index,
+            count,
+        ));
+
+        if key.final_incomplete_block && index + count > data_len {
+            // Final incomplete range block
+            let range_blocks = CompleteLargestRangeBlockIterator::new(
+                index,
+                data_len,
+                max_recursion_level,
+                sub_block_sqrt,
+            );
+            for (index, count) in range_blocks {
+                let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+                add_rect(&mut mesh, top_left, bottom_right, fill);
+            }
+            continue;
+        }
+
+        add_rect(&mut mesh, top_left, bottom_right, fill);
+
+        if key.split_diff_overlay
+            && diff_bytes(hex_app, key.file_index, key.other_file_index, index, count)
+                .is_some_and(|diff_bytes| diff_bytes > 0)
+        {
+            add_rect(
+                &mut mesh,
+                top_left,
+                bottom_right,
+                BlockShape::Outline(Color32::RED),
+            );
+        }
+    }
+
+    BlockMesh {
+        key,
+        zoom,
+        drawn_zoom: zoom,
+        mesh,
+        parts,
+        coverage,
+    }
+}
+
+/// Fades from 0.0 (at `size <= start`) to 1.0 (at `size >= end`).
+fn fade_in(size: f32, (start, end): (f32, f32)) -> f32 {
+    ((size - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+/// Colors range blocks in the current color mode: build one per frame (or mesh),
+/// rather than per range block.
+pub struct BlockColors<'a> {
+    hex_app: &'a HexApp,
+    /// For range blocks that `HexApp::variability_cache` doesn't cover,
+    /// in `ColorMode::Variability`.
+    variability: Option<RangeBlockVariability<'a>>,
+}
+
+impl<'a> BlockColors<'a> {
+    pub fn new(hex_app: &'a HexApp) -> Self {
+        let variability = (hex_app.color_mode == ColorMode::Variability
+            && hex_app.reference().is_some())
+        .then(|| hex_app.variability());
+
+        Self {
+            hex_app,
+            variability,
+        }
+    }
+
+    /// The fill color of a range block of the file at `file_index`.
+    pub fn color(
+        &self,
+        file_index: usize,
+        other_file_index: usize,
+        index: u64,
+        count: u64,
+    ) -> Color32 {
+        let hex_app = self.hex_app;
+        let Some(file) = hex_app.files.get(file_index) else {
+            return Color32::BLACK;
+        };
+
+        let scheme = hex_app.color_scheme;
+        match hex_app.color_mode {
+            ColorMode::Value | ColorMode::Semantic01 | ColorMode::Custom => {
+                let table = &hex_app.byte_color_table;
+                if !hex_app.color_averaging {
+                    to_color32(table[file.stats(index, count).average() as usize])
+                } else if let Some(palette) = hex_app.stats_palette() {
+                    to_color32(file.stats(index, count).average_color(palette))
+                } else {
+                    to_color32(file.palette_average_color(table, index, count))
+                }
+            }
+            ColorMode::Printable => {
+                printable_color(scheme, file.stats(index, count).printable_fraction())
+            }
+            ColorMode::Entropy => entropy_color(scheme, file.stats(index, count).sketch_entropy()),
+            ColorMode::Range => range_color(scheme, file.stats(index, count).range()),
+            ColorMode::Padding => {
+                let stats = file.stats(index, count);
+                padding_color(scheme, stats.zero_fraction(), stats.ff_fraction())
+            }
+            ColorMode::Uniform => uniform_color(scheme, file.stats(index, count).uniform_value()),
+            ColorMode::Diff => diff_color(
+                scheme,
+                diff_bytes(hex_app, file_index, other_file_index, index, count),
+                count,
+            ),
+            ColorMode::Variability => {
+                let variability =
+                    hex_app
+                        .variability_cache
+                        .get(index, count)
+                        .unwrap_or_else(|| {
+                            self.variability
+                                .as_ref()
+                                .map_or(0, |variability| variability.value(index, count))
+                        });
+                variability_color(scheme, variability.count_ones(), hex_app.files.len())
+            }
         }
     }
+}
 
-    ui.expand_to_include_rect(painter.clip_rect());
+/// The number of bytes in a range block that differ between two files
+/// (`None` if either file isn't loaded, or the range block is past the end of either file).
+pub fn diff_bytes(
+    hex_app: &HexApp,
+    file_index: usize,
+    other_file_index: usize,
+    index: u64,
+    count: u64,
+) -> Option<usize> {
+    let data = &hex_app.files.get(file_index)?.data;
+    let other_data = &hex_app.files.get(other_file_index)?.data;
+
+    hex_app
+        .diff_cache
+        .get(index, count)
+        .unwrap_or_else(|| RangeBlockDiff::new(data, other_data).value(index, count))
 }
 
 fn draw_range_border(
@@ -0,0 +1,112 @@
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+use range_blocks::{max_recursion_level, range_block_corners, range_block_size, CellCoords};
+
+use crate::hex_app::{
+    main_view::{self, draw_block_mesh, BlockMeshKey},
+    navigation::range_rect,
+    HexApp,
+};
+
+/// The minimum size (in points) of the range blocks drawn in the minimap.
+const MIN_BLOCK_SIZE: f32 = 3.0;
+
+/// Draws an overview of the whole active file, with the main view's viewport, the selection,
+/// and the differences from the reference file. Clicking or dragging pans the main view.
+pub fn minimap(hex_app: &mut HexApp, ui: &mut Ui) {
+    let Some(active) = hex_app.active() else {
+        return;
+    };
+    let data_len = active.data.len() as u64;
+    if data_len == 0 {
+        return;
+    }
+    let sub_block_sqrt = HexApp::SUB_BLOCK_SQRT;
+    let max_recursion_level = max_recursion_level(data_len, sub_block_sqrt);
+
+    // The size of the file's range blocks, in cells.
+    let Some(extent) = range_rect(0, data_len).map(|rect| rect.max.to_vec2()) else {
+        return;
+    };
+
+    let scale = ui.available_width() / extent.max_elem();
+    let (response, painter) = ui.allocate_painter(extent * scale, Sense::click_and_drag());
+    let origin = response.rect.min.to_vec2();
+    let minimap_rect = |top_left: CellCoords, bottom_right: CellCoords| {
+        let pos = |point: CellCoords| Pos2::new(point.x as f32, point.y as f32) * scale + origin;
+        Rect::from_two_pos(pos(top_left), pos(bottom_right))
+    };
+
+    painter.rect_filled(response.rect, 0.0, Color32::from_gray(24));
+
+    // The finest recursion level whose range blocks are large enough to see.
+    let recursion_level = (0..=max_recursion_level)
+        .find(|&level| sub_block_sqrt.pow(level) as f32 * scale >= MIN_BLOCK_SIZE)
+        .unwrap_or(max_recursion_level);
+    let key = BlockMeshKey::new(
+        hex_app,
+        &painter,
+        (hex_app.active_file, hex_app.reference_file),
+        recursion_level,
+        false,
+    );
+    let cached = hex_app.minimap_mesh.take();
+    hex_app.minimap_mesh = Some(draw_block_mesh(
+        hex_app, &painter, cached, key, scale, origin, 1.0,
+    ));
+
+    // Range blocks (at the minimap's recursion level) that contain differences, from the diff
+    // cache: bytes past the end of the reference file differ too.
+    let block_size = range_block_size(recursion_level, sub_block_sqrt);
+    let reference_len = hex_app.reference().map_or(0, |file| file.data.len() as u64);
+    let (file_index, other_file_index) = (hex_app.active_file, hex_app.reference_file);
+    if file_index != other_file_index && hex_app.reference().is_some() {
+        for index in (0..data_len).step_by(block_size as usize) {
+            let differs = (index + block_size).min(data_len) > reference_len
+                || main_view::diff_bytes(hex_app, file_index, other_file_index, index, block_size)
+                    .is_some_and(|diff_bytes| diff_bytes > 0);
+            if !differs {
+                continue;
+            }
+            let (top_left, bottom_right) = range_block_corners(index, block_size, sub_block_sqrt);
+            painter.rect_filled(
+                minimap_rect(top_left, bottom_right),
+                0.0,
+                Color32::from_rgba_unmultiplied(255, 0, 0, 160),
+            );
+        }
+    }
+
+    if let Some(selected_index) = hex_app.selected_index {
+        let index = selected_index as u64;
+        let count = u64::from(hex_app.hex_view_rows) * u64::from(hex_app.hex_view_columns);
+        if let Some(selection) = range_rect(index, count) {
+            let selection = Rect::from_min_max(
+                selection.min * scale + origin,
+                selection.max * scale + origin,
+            );
+            // (Expanded so that it stays visible at any scale.)
+            painter.rect_stroke(selection.expand(2.0), 0.0, Stroke::new(1.5, Color32::GOLD));
+        }
+    }
+
+    // The main view's viewport, in cells: see `main_view`'s `painter_coords`.
+    let half_size = hex_app.main_view_size / 2.0;
+    let viewport = Rect::from_min_max(
+        ((-half_size - hex_app.pan) / hex_app.zoom).to_pos2(),
+        ((half_size - hex_app.pan) / hex_app.zoom).to_pos2(),
+    );
+    painter.rect_stroke(
+        Rect::from_min_max(viewport.min * scale + origin, viewport.max * scale + origin),
+        0.0,
+        Stroke::new(1.5, Color32::WHITE),
+    );
+
+    if response.clicked() || response.dragged() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let cell = (pos - origin).to_vec2() / scale;
+            hex_app.pan = -cell * hex_app.zoom;
+            hex_app.pan_velocity = Vec2::ZERO;
+            hex_app.view_transition = None;
+        }
+    }
+}
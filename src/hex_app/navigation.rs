@@ -0,0 +1,235 @@
+use egui::emath::easing;
+use egui::{Context, Rect, Vec2};
+use range_blocks::{
+    get_cell_index, get_cell_offset, max_recursion_level, range_block_corners, range_block_size,
+    CellCoords, CompleteLargestRangeBlockIterator,
+};
+
+use crate::hex_app::{main_view, HexApp};
+
+/// Duration (in seconds) of animated zoom and pan transitions.
+const TRANSITION_DURATION: f64 = 0.4;
+/// Fraction of the main view filled by a range that is zoomed to.
+const FIT_MARGIN: f32 = 0.9;
+
+/// An animated transition of the main view's zoom and pan.
+pub struct ViewTransition {
+    /// Set by the first `update_view_transition` (the previous frame may have been long ago).
+    start_time: Option<f64>,
+    start_zoom: f32,
+    /// The cell at the center of the main view, at the start of the transition.
+    start_center: Vec2,
+    end_zoom: f32,
+    end_center: Vec2,
+}
+
+/// The bounding rectangle (in cells) of the range of `count` bytes starting at `index`.
+pub fn range_rect(index: u64, count: u64) -> Option<Rect> {
+    let sub_block_sqrt = HexApp::SUB_BLOCK_SQRT;
+    let max_recursion_level = max_recursion_level(index + count, sub_block_sqrt);
+
+    CompleteLargestRangeBlockIterator::new(
+        index,
+        index + count,
+        max_recursion_level,
+        sub_block_sqrt,
+    )
+    .map(|(index, count)| {
+        let (top_left, bottom_right) = range_block_corners(index, count, sub_block_sqrt);
+        Rect::from_min_max(
+            egui::pos2(top_left.x as f32, top_left.y as f32),
+            egui::pos2(bottom_right.x as f32, bottom_right.y as f32),
+        )
+    })
+    .reduce(|a, b| a.union(b))
+}
+
+/// Starts a transition to `zoom`, with `center` (in cells) at the center of the main view.
+fn animate_to(hex_app: &mut HexApp, zoom: f32, center: Vec2) {
+    hex_app.view_transition = Some(ViewTransition {
+        start_time: None,
+        start_zoom: hex_app.zoom,
+        start_center: -hex_app.pan / hex_app.zoom,
+        end_zoom: zoom.clamp(HexApp::MIN_ZOOM, HexApp::MAX_ZOOM),
+        end_center: center,
+    });
+    hex_app.pan_velocity = Vec2::ZERO;
+}
+
+/// Zooms the main view to fit the range of `count` bytes starting at `index`.
+pub fn zoom_to_range(hex_app: &mut HexApp, index: u64, count: u64) {
+    let Some(rect) = range_rect(index, count) else {
+        return;
+    };
+    let zoom = (hex_app.main_view_size / rect.size()).min_elem() * FIT_MARGIN;
+    animate_to(hex_app, zoom, rect.center().to_vec2());
+}
+
+/// Zooms the main view to fit the whole active file.
+pub fn fit_to_window(hex_app: &mut HexApp) {
+    if let Some(active) = hex_app.active() {
+        let len = active.data.len() as u64;
+        zoom_to_range(hex_app, 0, len);
+    }
+}
+
+/// Zooms the main view to the selected range block, if there is one.
+pub fn zoom_to_selected_block(hex_app: &mut HexApp) {
+    if let Some((index, count)) = hex_app.selected_range_block {
+        zoom_to_range(hex_app, index, count);
+    }
+}
+
+/// Zooms the main view to the selection, if there is one.
+pub fn zoom_to_selection(hex_app: &mut HexApp) {
+    if let Some(index) = hex_app.selected_index {
+        let count = u64::from(hex_app.hex_view_rows) * u64::from(hex_app.hex_view_columns);
+        zoom_to_range(hex_app, index as u64, count);
+    }
+}
+
+/// Zooms the main view out (for positive `steps`) or in by whole recursion levels,
+/// keeping the selected cell (or else the center of the view) in place.
+pub fn step_recursion_level(hex_app: &mut HexApp, steps: i32) {
+    let zoom = hex_app.zoom / (HexApp::SUB_BLOCK_SQRT as f32).powi(steps);
+    let zoom = zoom.clamp(HexApp::MIN_ZOOM, HexApp::MAX_ZOOM);
+
+    // `cell` stays at the same screen position: see `main_view`'s `painter_coords`.
+    let cell = match hex_app.selected_index {
+        Some(index) => {
+            let cell = get_cell_offset(index as u64, HexApp::SUB_BLOCK_SQRT);
+            Vec2::new(cell.x as f32, cell.y as f32) + Vec2::splat(0.5)
+        }
+        None => -hex_app.pan / hex_app.zoom,
+    };
+    let anchor = hex_app.pan + cell * hex_app.zoom;
+    let pan = anchor - cell * zoom;
+    animate_to(hex_app, zoom, -pan / zoom);
+}
+
+/// The recursion level of the range blocks drawn in the main view, if a file is loaded.
+pub fn rendered_recursion_level(hex_app: &HexApp) -> Option<u32> {
+    let data_len = hex_app.active()?.data.len() as u64;
+    let recursion_level = main_view::recursion_level(hex_app.main_view_size.x, hex_app.zoom);
+    let max_recursion_level = max_recursion_level(data_len, HexApp::SUB_BLOCK_SQRT);
+
+    Some(max_recursion_level.min(recursion_level.max(0.0) as u32))
+}
+
+/// Moves the cursor to the start of the range block at `recursion_level` starting at `index`,
+/// and animates to `zoom` with that block at the center of the main view.
+fn go_to_block(hex_app: &mut HexApp, index: u64, recursion_level: u32, zoom: f32) {
+    let count = range_block_size(recursion_level, HexApp::SUB_BLOCK_SQRT);
+    if let Some(rect) = range_rect(index, count) {
+        hex_app.selected_index = Some(index as usize);
+        animate_to(hex_app, zoom, rect.center().to_vec2());
+    }
+}
+
+/// Moves the cursor by (`dx`, `dy`): between sibling range blocks at the rendered recursion level
+/// if the main view has focus, or else by bytes and rows in the hex view.
+pub fn move_cursor(hex_app: &mut HexApp, (dx, dy): (i64, i64)) {
+    if !hex_app.main_view_focus {
+        let row_len = i64::from(hex_app.hex_view_columns);
+        set_cursor(hex_app, |index| {
+            index.saturating_add_signed((dx + dy * row_len) as isize)
+        });
+        return;
+    }
+
+    let sub_block_sqrt = HexApp::SUB_BLOCK_SQRT;
+    let (Some(recursion_level), Some(active)) =
+        (rendered_recursion_level(hex_app), hex_app.active())
+    else {
+        return;
+    };
+    let data_len = active.data.len() as u64;
+    let block_size = range_block_size(recursion_level, sub_block_sqrt);
+    let block_side = sub_block_sqrt.pow(recursion_level) as i64;
+    let cursor = hex_app.selected_index.unwrap_or(0) as u64;
+
+    let top_left = get_cell_offset(cursor - cursor % block_size, sub_block_sqrt);
+    let x = top_left.x.checked_add_signed(dx * block_side);
+    let y = top_left.y.checked_add_signed(dy * block_side);
+    let sibling_index = x
+        .zip(y)
+        .map(|(x, y)| get_cell_index(CellCoords { x, y }, sub_block_sqrt));
+
+    if let Some(index) = sibling_index.filter(|&index| index < data_len) {
+        go_to_block(hex_app, index, recursion_level, hex_app.zoom);
+    }
+}
+
+/// Moves the cursor to `new_index(cursor)`, clamped to the active file.
+pub fn set_cursor(hex_app: &mut HexApp, new_index: impl FnOnce(usize) -> usize) {
+    let Some(active) = hex_app.active() else {
+        return;
+    };
+    let last_index = active.data.len().saturating_sub(1);
+    let index = new_index(hex_app.selected_index.unwrap_or(0));
+    hex_app.selected_index = Some(index.min(last_index));
+}
+
+/// Descends from the cursor's range block (at the rendered recursion level) into its first sub-block.
+pub fn enter_block(hex_app: &mut HexApp) {
+    let Some(recursion_level) = rendered_recursion_level(hex_app).filter(|&level| level > 0) else {
+        return;
+    };
+    let block_size = range_block_size(recursion_level, HexApp::SUB_BLOCK_SQRT);
+    let cursor = hex_app.selected_index.unwrap_or(0) as u64;
+    let zoom = hex_app.zoom * HexApp::SUB_BLOCK_SQRT as f32;
+
+    go_to_block(
+        hex_app,
+        cursor - cursor % block_size,
+        recursion_level - 1,
+        zoom,
+    );
+}
+
+/// Goes up from the cursor's range block (at the rendered recursion level) to its parent block.
+pub fn leave_block(hex_app: &mut HexApp) {
+    let (Some(recursion_level), Some(active)) =
+        (rendered_recursion_level(hex_app), hex_app.active())
+    else {
+        return;
+    };
+    let data_len = active.data.len() as u64;
+    if recursion_level >= max_recursion_level(data_len, HexApp::SUB_BLOCK_SQRT) {
+        return;
+    }
+    let parent_size = range_block_size(recursion_level + 1, HexApp::SUB_BLOCK_SQRT);
+    let cursor = hex_app.selected_index.unwrap_or(0) as u64;
+    let zoom = hex_app.zoom / HexApp::SUB_BLOCK_SQRT as f32;
+
+    go_to_block(
+        hex_app,
+        cursor - cursor % parent_size,
+        recursion_level + 1,
+        zoom,
+    );
+}
+
+/// Advances the current transition (if any) to `time`.
+pub fn update_view_transition(hex_app: &mut HexApp, ctx: &Context, time: f64) {
+    let Some(transition) = &mut hex_app.view_transition else {
+        return;
+    };
+
+    let start_time = *transition.start_time.get_or_insert(time);
+    let t = ((time - start_time) / TRANSITION_DURATION).clamp(0.0, 1.0) as f32;
+    let eased = easing::cubic_in_out(t);
+
+    // Interpolating zoom geometrically makes zooming feel like it has a constant speed.
+    let zoom = transition.start_zoom * (transition.end_zoom / transition.start_zoom).powf(eased);
+    let center =
+        transition.start_center + (transition.end_center - transition.start_center) * eased;
+    hex_app.zoom = zoom;
+    hex_app.pan = -center * zoom;
+
+    if t < 1.0 {
+        ctx.request_repaint();
+    } else {
+        hex_app.view_transition = None;
+    }
+}
@@ -0,0 +1,414 @@
+use egui::{vec2, ComboBox, Context, DragValue, Grid, Rect, Sense, Ui, Window};
+use range_blocks::color::{
+    byte_color, ByteClass, ByteRule, GradientStop, PaletteScheme, Rgb, UserPalette,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{hex_app::HexApp, utilities::to_color32};
+
+/// The user-defined palettes used by `ColorMode::Custom` (saved with the app settings).
+#[derive(Serialize, Deserialize)]
+pub struct Palettes {
+    palettes: Vec<UserPalette>,
+    /// Index of the palette in use.
+    selected: usize,
+    /// Path of the palette file to save or load.
+    #[serde(skip)]
+    path: String,
+    /// An existing file the user chose to replace.
+    #[serde(skip)]
+    overwrite_path: Option<String>,
+    /// An existing file that saving stopped at, to offer overwriting it.
+    #[serde(skip)]
+    existing_path: Option<String>,
+    #[serde(skip)]
+    status: String,
+    /// Incremented whenever the palette in use changes.
+    #[serde(skip)]
+    revision: u64,
+    /// The time (`egui::InputState::time`) of the last change to the palette in use.
+    #[serde(skip)]
+    last_change: f64,
+}
+
+impl Default for Palettes {
+    fn default() -> Self {
+        let rule = |class, (r, g, b)| ByteRule {
+            class,
+            color: Rgb::new(r, g, b),
+        };
+        let byte_classes = UserPalette {
+            name: "Byte classes".to_string(),
+            scheme: PaletteScheme::Rules {
+                rules: vec![
+                    rule(ByteClass::Zero, (0, 0, 0)),
+                    rule(ByteClass::Ff, (255, 255, 255)),
+                    rule(ByteClass::Whitespace, (0, 160, 160)),
+                    rule(ByteClass::Printable, (64, 128, 255)),
+                    rule(ByteClass::Control, (255, 128, 0)),
+                    rule(ByteClass::HighBit, (192, 64, 192)),
+                ],
+                fallback: Rgb::GRAY,
+            },
+        };
+        let grayscale = UserPalette {
+            name: "Grayscale".to_string(),
+            scheme: PaletteScheme::Gradient {
+                stops: vec![
+                    GradientStop {
+                        byte: 0x00,
+                        color: Rgb::BLACK,
+                    },
+                    GradientStop {
+                        byte: 0xFF,
+                        color: Rgb::new(255, 255, 255),
+                    },
+                ],
+            },
+        };
+
+        Self {
+            palettes: vec![byte_classes, grayscale],
+            selected: 0,
+            path: String::new(),
+            overwrite_path: None,
+            existing_path: None,
+            status: String::new(),
+            revision: 0,
+            last_change: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Palettes {
+    /// The key that the palettes are saved under in eframe's storage.
+    pub const STORAGE_KEY: &'static str = "palettes";
+
+    /// Seconds after the last change that the palette in use is considered settled.
+    pub const SETTLE_TIME: f64 = 0.5;
+
+    /// The color table of the palette in use (`byte_color`, if there are no palettes).
+    pub fn color_table(&self) -> [Rgb; 256] {
+        match self.palettes.get(self.selected) {
+            Some(palette) => palette.color_table(),
+            None => std::array::from_fn(|byte| byte_color(byte as u8)),
+        }
+    }
+
+    /// A number that changes whenever the palette in use changes.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Whether the palette in use hasn't changed for `SETTLE_TIME` seconds before `time`, so
+    /// edits (e.g. dragging a color picker) are likely complete.
+    pub fn settled(&self, time: f64) -> bool {
+        time - self.last_change >= Self::SETTLE_TIME
+    }
+
+    /// The name of the palette in use.
+    pub fn selected_name(&self) -> Option<&str> {
+        Some(&self.palettes.get(self.selected)?.name)
+    }
+
+    /// Adds `palette` and selects it.
+    fn add(&mut self, palette: UserPalette) {
+        self.palettes.push(palette);
+        self.selected = self.palettes.len() - 1;
+    }
+
+    /// Writes the selected palette to `path`. Returns a status message.
+    /// Existing files are only replaced once the user has chosen to overwrite them.
+    fn save(&mut self) -> String {
+        self.existing_path = None;
+        let Some(palette) = self.palettes.get(self.selected) else {
+            return "no palette selected".to_string();
+        };
+        if self.path.trim().is_empty() {
+            return "Save failed: no path (e.g. palette.ron)".to_string();
+        }
+        if self.overwrite_path.as_ref() != Some(&self.path)
+            && std::path::Path::new(&self.path).exists()
+        {
+            self.existing_path = Some(self.path.clone());
+            return format!("{} already exists", self.path);
+        }
+        let text = match ron::ser::to_string_pretty(palette, ron::ser::PrettyConfig::default()) {
+            Ok(text) => text,
+            Err(e) => return format!("Save failed: {e}"),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = &self.path;
+            match std::fs::write(path, text) {
+                Ok(()) => format!("Saved {} to {path}", palette.name),
+                Err(e) => format!("Save failed: {e}"),
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = text;
+            "Saving files isn't supported on the web".to_string()
+        }
+    }
+
+    /// Adds the palette saved at `path`. Returns a status message.
+    fn load(&mut self) -> String {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let text = match std::fs::read_to_string(&self.path) {
+                Ok(text) => text,
+                Err(e) => return format!("Load failed: {e}"),
+            };
+            match ron::from_str::<UserPalette>(&text) {
+                Ok(palette) => {
+                    let status = format!("Loaded {} from {}", palette.name, self.path);
+                    self.add(palette);
+                    status
+                }
+                Err(e) => format!("Load failed: {e}"),
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            "Loading files isn't supported on the web".to_string()
+        }
+    }
+}
+
+/// Draws the palette editor window (opened via bottom bar button).
+pub fn palette_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.palette_window;
+
+    let palettes = &hex_app.palettes;
+    let previous = (
+        palettes.selected,
+        palettes.palettes.get(palettes.selected).cloned(),
+    );
+
+    Window::new("Palettes").open(&mut open).show(ctx, |ui| {
+        let palettes = &mut hex_app.palettes;
+        ui.label("Used by the Custom color mode.");
+
+        ui.horizontal(|ui| {
+            let names: Vec<String> = palettes.palettes.iter().map(|p| p.name.clone()).collect();
+            ComboBox::from_id_salt("palette")
+                .selected_text(names.get(palettes.selected).cloned().unwrap_or_default())
+                .show_ui(ui, |ui| {
+                    for (index, name) in names.into_iter().enumerate() {
+                        ui.selectable_value(&mut palettes.selected, index, name);
+                    }
+                });
+
+            if ui.button("New rules").clicked() {
+                palettes.add(UserPalette {
+                    name: "New rules".to_string(),
+                    scheme: PaletteScheme::Rules {
+                        rules: Vec::new(),
+                        fallback: Rgb::GRAY,
+                    },
+                });
+            }
+            if ui.button("New gradient").clicked() {
+                palettes.add(UserPalette {
+                    name: "New gradient".to_string(),
+                    scheme: PaletteScheme::Gradient { stops: Vec::new() },
+                });
+            }
+            if let Some(palette) = palettes.palettes.get(palettes.selected) {
+                if ui.button("Duplicate").clicked() {
+                    let copy = UserPalette {
+                        name: format!("{} copy", palette.name),
+                        ..palette.clone()
+                    };
+                    palettes.add(copy);
+                }
+                if ui.button("Delete").clicked() {
+                    palettes.palettes.remove(palettes.selected);
+                    palettes.selected = palettes.selected.saturating_sub(1);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Path:");
+            ui.text_edit_singleline(&mut palettes.path);
+            if ui
+                .button("Save")
+                .on_hover_text("Save the selected palette")
+                .clicked()
+            {
+                palettes.status = palettes.save();
+            }
+            if ui.button("Load").clicked() {
+                palettes.status = palettes.load();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(&palettes.status);
+            if palettes.existing_path.as_ref() == Some(&palettes.path)
+                && ui.button("Overwrite").clicked()
+            {
+                palettes.overwrite_path = palettes.existing_path.take();
+                palettes.status = palettes.save();
+            }
+        });
+        ui.separator();
+
+        let Some(palette) = palettes.palettes.get_mut(palettes.selected) else {
+            ui.label("no palette selected");
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut palette.name);
+        });
+        palette_preview(ui, palette);
+
+        match &mut palette.scheme {
+            PaletteScheme::Rules { rules, fallback } => rules_editor(ui, rules, fallback),
+            PaletteScheme::Gradient { stops } => gradient_editor(ui, stops),
+        }
+    });
+
+    let palettes = &mut hex_app.palettes;
+    if previous.1.as_ref() != palettes.palettes.get(palettes.selected)
+        || previous.0 != palettes.selected
+    {
+        palettes.revision += 1;
+        palettes.last_change = ctx.input(|i| i.time);
+    }
+
+    hex_app.palette_window = open;
+}
+
+/// Draws a strip of the palette's colors for every byte value, from 0x00 to 0xFF.
+fn palette_preview(ui: &mut Ui, palette: &UserPalette) {
+    let (rect, _) = ui.allocate_exact_size(vec2(512.0, 16.0), Sense::hover());
+    let width = rect.width() / 256.0;
+    for (byte, color) in palette.color_table().into_iter().enumerate() {
+        let min = rect.min + vec2(byte as f32 * width, 0.0);
+        let cell = Rect::from_min_size(min, vec2(width, rect.height()));
+        ui.painter().rect_filled(cell, 0.0, to_color32(color));
+    }
+}
+
+/// Draws a color picker button for `color`.
+fn color_edit(ui: &mut Ui, color: &mut Rgb) {
+    let mut rgb = [color.r, color.g, color.b];
+    if ui.color_edit_button_srgb(&mut rgb).changed() {
+        *color = Rgb::new(rgb[0], rgb[1], rgb[2]);
+    }
+}
+
+/// Draws a hexadecimal byte value editor.
+fn byte_edit(ui: &mut Ui, byte: &mut u8) {
+    ui.add(
+        DragValue::new(byte)
+            .hexadecimal(2, false, true)
+            .prefix("0x"),
+    );
+}
+
+/// An edit to a list item, applied after the list is drawn.
+enum ListEdit {
+    MoveUp(usize),
+    Remove(usize),
+}
+
+fn rules_editor(ui: &mut Ui, rules: &mut Vec<ByteRule>, fallback: &mut Rgb) {
+    ui.label("The first rule that matches a byte sets its color.");
+    let mut edit = None;
+
+    Grid::new("palette_rules")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, rule) in rules.iter_mut().enumerate() {
+                ComboBox::from_id_salt(("rule_class", index))
+                    .selected_text(rule.class.name())
+                    .show_ui(ui, |ui| {
+                        let range = matches!(rule.class, ByteClass::Range { .. });
+                        if ui.selectable_label(range, "Range").clicked() && !range {
+                            rule.class = ByteClass::Range {
+                                start: 0x00,
+                                end: 0xFF,
+                            };
+                        }
+                        for class in ByteClass::PREDICATES {
+                            ui.selectable_value(&mut rule.class, class, class.name());
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    if let ByteClass::Range { start, end } = &mut rule.class {
+                        byte_edit(ui, start);
+                        ui.label("to");
+                        byte_edit(ui, end);
+                    }
+                });
+                color_edit(ui, &mut rule.color);
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(index > 0, egui::Button::new("⏶")).clicked() {
+                        edit = Some(ListEdit::MoveUp(index));
+                    }
+                    if ui.button("🗑").on_hover_text("Remove").clicked() {
+                        edit = Some(ListEdit::Remove(index));
+                    }
+                });
+                ui.end_row();
+            }
+
+            ui.label("Otherwise");
+            ui.label("");
+            color_edit(ui, fallback);
+            ui.end_row();
+        });
+
+    match edit {
+        Some(ListEdit::MoveUp(index)) => rules.swap(index - 1, index),
+        Some(ListEdit::Remove(index)) => {
+            rules.remove(index);
+        }
+        None => {}
+    }
+    if ui.button("Add rule").clicked() {
+        rules.push(ByteRule {
+            class: ByteClass::Printable,
+            color: Rgb::LIGHT_GREEN,
+        });
+    }
+}
+
+fn gradient_editor(ui: &mut Ui, stops: &mut Vec<GradientStop>) {
+    ui.label("Colors are interpolated between the stops on either side of each byte.");
+    let mut remove = None;
+
+    Grid::new("palette_stops")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, stop) in stops.iter_mut().enumerate() {
+                byte_edit(ui, &mut stop.byte);
+                color_edit(ui, &mut stop.color);
+                if ui.button("🗑").on_hover_text("Remove").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = remove {
+        stops.remove(index);
+    }
+    if ui.button("Add stop").clicked() {
+        stops.push(GradientStop {
+            byte: 0xFF,
+            color: Rgb::new(255, 255, 255),
+        });
+    }
+}
@@ -0,0 +1,135 @@
+use egui::{ComboBox, Context, Window};
+
+use crate::{
+    hex_app::{loaded_file::LoadedFile, HexApp},
+    patch::PatchFormat,
+};
+
+/// Draws the window for exporting a patch from the active file to the reference file,
+/// and for applying patches to the active file (opened via bottom bar button).
+pub fn patch_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.patch_window;
+
+    Window::new("Patch").open(&mut open).show(ctx, |ui| {
+        let (Some(active), Some(reference)) = (hex_app.active(), hex_app.reference()) else {
+            ui.label("no files loaded");
+            return;
+        };
+        ui.label(format!(
+            "Patch from {} to {}",
+            active.label(hex_app.active_file),
+            reference.label(hex_app.reference_file)
+        ));
+
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            ComboBox::from_id_salt("patch_format")
+                .selected_text(hex_app.patch_format.name())
+                .show_ui(ui, |ui| {
+                    for format in PatchFormat::ALL {
+                        ui.selectable_value(&mut hex_app.patch_format, format, format.name());
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Path:");
+            ui.text_edit_singleline(&mut hex_app.patch_path);
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Export").clicked() {
+                hex_app.patch_status = export_patch(hex_app);
+            }
+            if ui
+                .button("Apply")
+                .on_hover_text("Apply the patch at this path to the active file")
+                .clicked()
+            {
+                hex_app.patch_status = apply_patch_path(hex_app);
+            }
+        });
+
+        ui.label("Patch files dropped while this window is open are applied to the active file.");
+        ui.separator();
+        ui.label(&hex_app.patch_status);
+    });
+
+    hex_app.patch_window = open;
+}
+
+/// Writes a patch from the active file to the reference file to `patch_path`.
+/// Returns a status message.
+fn export_patch(hex_app: &HexApp) -> String {
+    let (Some(active), Some(reference)) = (hex_app.active(), hex_app.reference()) else {
+        return "no files loaded".to_string();
+    };
+
+    let patch = match hex_app.patch_format.create(&active.data, &reference.data) {
+        Ok(patch) => patch,
+        Err(e) => return format!("Export failed: {e}"),
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = if hex_app.patch_path.is_empty() {
+            format!("patch.{}", hex_app.patch_format.extension())
+        } else {
+            hex_app.patch_path.clone()
+        };
+        match std::fs::write(&path, &patch) {
+            Ok(()) => format!("Wrote {} byte patch to {path}", patch.len()),
+            Err(e) => format!("Export failed: {e}"),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        format!(
+            "Created {} byte patch, but saving files isn't supported on the web",
+            patch.len()
+        )
+    }
+}
+
+/// Reads the patch at `patch_path` and applies it to the active file.
+/// Returns a status message.
+fn apply_patch_path(hex_app: &mut HexApp) -> String {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        match std::fs::read(&hex_app.patch_path) {
+            Ok(patch) => {
+                let patch_name = hex_app.patch_path.clone();
+                apply_patch(hex_app, &patch_name, &patch)
+            }
+            Err(e) => format!("Reading {} failed: {e}", hex_app.patch_path),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = hex_app;
+        "Reading files isn't supported on the web: drop the patch file instead".to_string()
+    }
+}
+
+/// Applies `patch` to the active file, loading the result as a new file.
+/// Returns a status message.
+pub fn apply_patch(hex_app: &mut HexApp, patch_name: &str, patch: &[u8]) -> String {
+    let Some(active) = hex_app.active() else {
+        return "no files loaded".to_string();
+    };
+
+    let format = PatchFormat::detect(patch);
+    match format.apply(patch, &active.data) {
+        Ok(data) => {
+            let name = format!(
+                "{} + {patch_name}",
+                active.source_name.as_deref().unwrap_or("patched")
+            );
+            hex_app.add_file(LoadedFile::new(Some(name), data, &hex_app.cache_settings));
+            format!("Applied {} patch {patch_name}", format.name())
+        }
+        Err(e) => format!("Applying {} patch {patch_name} failed: {e}", format.name()),
+    }
+}
@@ -0,0 +1,138 @@
+use egui::{Color32, Context, Grid, Rect, Sense, Ui, Vec2, Window};
+use range_blocks::{color::ColorScheme, distribution::ByteDistribution};
+
+use crate::{hex_app::HexApp, utilities::to_color32};
+
+/// Number of most common byte values listed.
+const MOST_COMMON_COUNT: usize = 5;
+const HISTOGRAM_HEIGHT: f32 = 100.0;
+
+/// Draws the window showing byte value statistics of the active and reference files,
+/// over the same range (opened via bottom bar button).
+pub fn statistics_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.statistics_window;
+
+    Window::new("Statistics").open(&mut open).show(ctx, |ui| {
+        hex_app.statistics_range.combo_box(ui);
+
+        let Some((index, len)) = hex_app.target_range(hex_app.statistics_range) else {
+            ui.label("nothing selected");
+            return;
+        };
+        ui.label(format!("0x{index:08X}; size: {len} bytes"));
+        ui.separator();
+
+        let mut file_indices = vec![hex_app.active_file];
+        if hex_app.reference_file != hex_app.active_file {
+            file_indices.push(hex_app.reference_file);
+        }
+
+        ui.columns(file_indices.len(), |columns| {
+            for (ui, &file_index) in columns.iter_mut().zip(&file_indices) {
+                let Some(file) = hex_app.files.get(file_index) else {
+                    continue;
+                };
+                ui.strong(file.label(file_index));
+
+                let distribution = file.distribution(index, len);
+                if distribution.is_empty() {
+                    ui.label("past the end of the file");
+                    continue;
+                }
+
+                histogram(ui, &distribution, hex_app.color_scheme);
+                statistics_grid(ui, file_index, &distribution);
+            }
+        });
+    });
+
+    hex_app.statistics_window = open;
+}
+
+/// Draws a 256-bin histogram of byte values, with a tooltip for the hovered bin.
+fn histogram(ui: &mut Ui, distribution: &ByteDistribution, scheme: ColorScheme) {
+    let size = Vec2::new(ui.available_width().max(256.0), HISTOGRAM_HEIGHT);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let rect = response.rect;
+    let bin_width = rect.width() / 256.0;
+    let max_count = distribution
+        .counts
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    painter.rect_filled(rect, 0.0, Color32::from_gray(24));
+
+    for (byte, &count) in distribution.counts.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let height = (rect.height() * count as f32 / max_count as f32).max(1.0);
+        let left = rect.left() + byte as f32 * bin_width;
+        let bar = Rect::from_min_max(
+            egui::pos2(left, rect.bottom() - height),
+            egui::pos2(left + bin_width, rect.bottom()),
+        );
+        // Keep black (0x00) visible against the background.
+        let color = if byte == 0 {
+            Color32::from_gray(96)
+        } else {
+            to_color32(scheme.byte_color(byte as u8))
+        };
+        painter.rect_filled(bar, 0.0, color);
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        let byte = (((pos.x - rect.left()) / bin_width) as usize).min(255);
+        response
+            .on_hover_text_at_pointer(format!("0x{byte:02X}: {} bytes", distribution.counts[byte]));
+    }
+}
+
+fn statistics_grid(ui: &mut Ui, file_index: usize, distribution: &ByteDistribution) {
+    let len = distribution.len();
+
+    Grid::new(("statistics_grid", file_index))
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Bytes");
+            ui.monospace(len.to_string());
+            ui.end_row();
+
+            ui.label("Mean");
+            ui.monospace(format!("{:.4}", distribution.mean()))
+                .on_hover_text("127.5 for random data");
+            ui.end_row();
+
+            ui.label("Entropy");
+            ui.monospace(format!("{:.6} bits/byte", distribution.entropy()));
+            ui.end_row();
+
+            ui.label("Chi-square");
+            ui.monospace(format!("{:.2}", distribution.chi_square()))
+                .on_hover_text("Against uniformly distributed byte values: 255 degrees of freedom");
+            ui.end_row();
+
+            ui.label("Serial correlation");
+            ui.monospace(match distribution.serial_correlation() {
+                Some(correlation) => format!("{correlation:.6}"),
+                None => "undefined".to_string(),
+            })
+            .on_hover_text("0.0 for random data");
+            ui.end_row();
+
+            ui.label("Most common");
+            ui.vertical(|ui| {
+                for (byte, count) in distribution.most_common(MOST_COMMON_COUNT) {
+                    ui.monospace(format!(
+                        "0x{byte:02X}: {count} ({:.2}%)",
+                        100.0 * count as f64 / len as f64
+                    ));
+                }
+            });
+            ui.end_row();
+        });
+}
@@ -0,0 +1,228 @@
+use egui::{Context, DragValue, Grid, RichText, ScrollArea, TextEdit, TextStyle, Ui, Window};
+use range_blocks::strings::{find_strings, FoundString, StringCoverage, StringEncoding};
+
+use crate::hex_app::HexApp;
+
+/// Number of characters of each string shown in the table (the rest are shown on hover).
+const PREVIEW_LEN: usize = 64;
+
+/// The columns that the strings table can be sorted by.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SortColumn {
+    Offset,
+    Length,
+    Encoding,
+    Text,
+}
+
+/// Options and results of the strings window.
+pub struct Strings {
+    encodings: Vec<StringEncoding>,
+    /// Minimum string length, in characters.
+    min_len: usize,
+    /// The (file index, encodings, min_len) that `found` was extracted with.
+    found_for: Option<(usize, Vec<StringEncoding>, usize)>,
+    found: Vec<FoundString>,
+    filter: String,
+    sort_column: SortColumn,
+    sort_descending: bool,
+    /// Indices into `found` of the strings that match `filter`, in sort order.
+    rows: Vec<usize>,
+    /// The bytes covered by the strings that match `filter`.
+    coverage: StringCoverage,
+    highlight: bool,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self {
+            encodings: vec![StringEncoding::Ascii, StringEncoding::Utf16Le],
+            min_len: 4,
+            found_for: None,
+            found: Vec::new(),
+            filter: String::new(),
+            sort_column: SortColumn::Offset,
+            sort_descending: false,
+            rows: Vec::new(),
+            coverage: StringCoverage::default(),
+            highlight: true,
+        }
+    }
+}
+
+impl Strings {
+    /// Limit on the number of strings extracted from a file.
+    const MAX_STRINGS: usize = 100_000;
+
+    /// Forces the strings to be extracted again (e.g. after file indices have shifted).
+    pub fn invalidate(&mut self) {
+        self.found_for = None;
+    }
+
+    /// The file whose strings should be highlighted in the main view, if any.
+    pub fn highlighted_file(&self) -> Option<usize> {
+        let (file_index, ..) = self.found_for.as_ref().filter(|_| self.highlight)?;
+        Some(*file_index)
+    }
+
+    /// The number of bytes in the range block that are part of a listed string.
+    pub fn covered_len(&self, index: u64, count: u64) -> u64 {
+        self.coverage.covered_len(index, count)
+    }
+
+    /// Extracts the strings of `data` if they haven't been already.
+    fn extract(&mut self, file_index: usize, data: &[u8]) {
+        let found_for = Some((file_index, self.encodings.clone(), self.min_len));
+        if self.found_for != found_for {
+            self.found = find_strings(data, &self.encodings, self.min_len, Self::MAX_STRINGS);
+            self.found_for = found_for;
+            self.update_rows();
+        }
+    }
+
+    /// Filters and sorts the table rows.
+    fn update_rows(&mut self) {
+        let filter = self.filter.to_lowercase();
+        let found = &self.found;
+        self.rows = (0..found.len())
+            .filter(|&i| filter.is_empty() || found[i].text.to_lowercase().contains(&filter))
+            .collect();
+
+        match self.sort_column {
+            SortColumn::Offset => {}
+            SortColumn::Length => self.rows.sort_by_key(|&i| found[i].len),
+            SortColumn::Encoding => self.rows.sort_by_key(|&i| found[i].encoding),
+            SortColumn::Text => self
+                .rows
+                .sort_by(|&a, &b| found[a].text.cmp(&found[b].text)),
+        }
+        if self.sort_descending {
+            self.rows.reverse();
+        }
+
+        self.coverage = StringCoverage::new(self.rows.iter().map(|&i| &found[i]));
+    }
+
+    /// Draws a column header that sorts the table by `column` when clicked.
+    fn sort_header(&mut self, ui: &mut Ui, column: SortColumn, name: &str) {
+        let sorted = self.sort_column == column;
+        let text = match (sorted, self.sort_descending) {
+            (false, _) => name.to_string(),
+            (true, false) => format!("{name} ⏶"),
+            (true, true) => format!("{name} ⏷"),
+        };
+
+        if ui
+            .selectable_label(sorted, RichText::new(text).strong())
+            .clicked()
+        {
+            self.sort_descending = sorted && !self.sort_descending;
+            self.sort_column = column;
+            self.update_rows();
+        }
+    }
+}
+
+/// Draws the window listing the strings found in the active file (opened via bottom bar button).
+pub fn strings_window(hex_app: &mut HexApp, ctx: &Context) {
+    let mut open = hex_app.strings_window;
+
+    Window::new("Strings").open(&mut open).show(ctx, |ui| {
+        let strings = &mut hex_app.strings;
+
+        ui.horizontal(|ui| {
+            for encoding in StringEncoding::ALL {
+                let mut enabled = strings.encodings.contains(&encoding);
+                if ui.checkbox(&mut enabled, encoding.name()).changed() {
+                    strings.encodings.retain(|&e| e != encoding);
+                    if enabled {
+                        strings.encodings.push(encoding);
+                        strings.encodings.sort();
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Minimum length:");
+            ui.add(DragValue::new(&mut strings.min_len).range(1..=256));
+            ui.checkbox(&mut strings.highlight, "Highlight in main view");
+        });
+        let filter = TextEdit::singleline(&mut strings.filter).hint_text("Filter");
+        if ui.add(filter).changed() {
+            strings.update_rows();
+        }
+
+        let Some(active) = hex_app.files.get(hex_app.active_file) else {
+            ui.label("no file loaded");
+            return;
+        };
+        strings.extract(hex_app.active_file, &active.data);
+
+        let limit_note = if strings.found.len() == Strings::MAX_STRINGS {
+            " (limit reached)"
+        } else {
+            ""
+        };
+        ui.label(format!(
+            "{} of {} strings{limit_note} in {}",
+            strings.rows.len(),
+            strings.found.len(),
+            active.label(hex_app.active_file)
+        ));
+        ui.separator();
+
+        let mut go_to_index = None;
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        // The headers are in a grid of their own, so that they don't scroll: the same minimum
+        // column width (that of an offset, plus a little) keeps the two grids' columns aligned.
+        let char_width =
+            ui.fonts(|f| f.glyph_width(&TextStyle::Monospace.resolve(ui.style()), '0'));
+        let column_width = char_width * 12.0;
+
+        Grid::new("strings_header")
+            .num_columns(4)
+            .min_col_width(column_width)
+            .show(ui, |ui| {
+                strings.sort_header(ui, SortColumn::Offset, "Offset");
+                strings.sort_header(ui, SortColumn::Length, "Length");
+                strings.sort_header(ui, SortColumn::Encoding, "Encoding");
+                strings.sort_header(ui, SortColumn::Text, "Text");
+                ui.end_row();
+            });
+
+        ScrollArea::vertical().show_rows(ui, row_height, strings.rows.len(), |ui, row_range| {
+            Grid::new("strings_grid")
+                .striped(true)
+                .num_columns(4)
+                .min_col_width(column_width)
+                .show(ui, |ui| {
+                    for &i in &strings.rows[row_range] {
+                        let string = &strings.found[i];
+                        let selected = hex_app
+                            .selected_index
+                            .is_some_and(|index| index as u64 == string.index);
+                        let offset = RichText::new(format!("0x{:08X}", string.index)).monospace();
+
+                        if ui.selectable_label(selected, offset).clicked() {
+                            go_to_index = Some(string.index as usize);
+                        }
+                        ui.monospace(string.len.to_string());
+                        ui.label(string.encoding.name());
+                        if string.text.chars().count() > PREVIEW_LEN {
+                            let preview: String = string.text.chars().take(PREVIEW_LEN).collect();
+                            ui.monospace(preview + "…").on_hover_text(&string.text);
+                        } else {
+                            ui.monospace(&string.text);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some(index) = go_to_index {
+            hex_app.go_to_index(index);
+        }
+    });
+
+    hex_app.strings_window = open;
+}
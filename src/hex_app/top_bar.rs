@@ -1,37 +1,60 @@
-use egui::{Key, KeyboardShortcut, Modifiers};
+use crate::hex_app::{commands::Command, HexApp};
+use range_blocks::color::ColorScheme;
 
-use crate::hex_app::HexApp;
-
-use super::{CellViewMode, ColorMode, WhichFile};
+use super::{CellViewMode, ColorMode, MainViewLayout};
 
 // Draws the control bar at the top of the window.
 pub fn top_bar(hex_app: &mut HexApp, ctx: &egui::Context, ui: &mut egui::Ui) {
-    // Keyboard shortcuts for some of these controls.
-    ctx.input_mut(|i| {
-        if i.consume_shortcut(&KeyboardShortcut::new(Modifiers::NONE, Key::F)) {
-            hex_app.active_file = hex_app.active_file.next();
-        }
-
-        if i.consume_shortcut(&KeyboardShortcut::new(Modifiers::NONE, Key::V)) {
-            hex_app.cell_view_mode = hex_app.cell_view_mode.next();
-        }
-        if i.consume_shortcut(&KeyboardShortcut::new(Modifiers::NONE, Key::C)) {
-            hex_app.color_mode = hex_app.color_mode.next();
-        }
-    });
-
     ui.horizontal(|ui| {
         ui.heading("hex diff test (egui UI)");
         ui.separator();
-        ui.selectable_value(&mut hex_app.active_file, WhichFile::File0, "File0");
-        ui.selectable_value(&mut hex_app.active_file, WhichFile::File1, "File1");
+        file_combo_box(hex_app, ui, "active_file", "Active:", |hex_app| {
+            &mut hex_app.active_file
+        });
+        file_combo_box(hex_app, ui, "reference_file", "Reference:", |hex_app| {
+            &mut hex_app.reference_file
+        });
+        if ui
+            .add_enabled(hex_app.files.len() > 1, egui::Button::new("Close"))
+            .on_hover_text("Unload the active file")
+            .clicked()
+        {
+            hex_app.close_active_file();
+        }
         ui.separator();
         ui.label("zoom: ");
-        ui.add(
+        let zoom = ui.add(
             egui::DragValue::new(&mut hex_app.zoom)
                 .speed(0.01)
                 .range(HexApp::MIN_ZOOM..=HexApp::MAX_ZOOM),
         );
+        if zoom.changed() {
+            hex_app.view_transition = None;
+        }
+        let navigation_buttons = [
+            ("Fit", Command::FitToWindow, true),
+            (
+                "Block",
+                Command::ZoomToSelectedBlock,
+                hex_app.selected_range_block.is_some(),
+            ),
+            (
+                "Selection",
+                Command::ZoomToSelection,
+                hex_app.selected_index.is_some(),
+            ),
+            ("−", Command::LevelUp, true),
+            ("+", Command::LevelDown, true),
+        ];
+        for (text, command, enabled) in navigation_buttons {
+            if ui
+                .add_enabled(enabled, egui::Button::new(text))
+                .on_hover_text(hex_app.keybindings.hover_text(ctx, command))
+                .clicked()
+            {
+                command.run(hex_app);
+            }
+        }
         ui.separator();
 
         ui.label("Cell View Mode:");
@@ -48,10 +71,83 @@ pub fn top_bar(hex_app: &mut HexApp, ctx: &egui::Context, ui: &mut egui::Ui) {
             ColorMode::Semantic01,
             "Semantic 01",
         );
+        ui.selectable_value(
+            &mut hex_app.color_mode,
+            ColorMode::Variability,
+            "Variability",
+        );
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Printable, "Printable");
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Entropy, "Entropy");
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Range, "Range");
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Padding, "Padding");
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Uniform, "Uniform");
+        ui.selectable_value(&mut hex_app.color_mode, ColorMode::Custom, "Custom");
+
+        ui.separator();
+
+        ui.label("Color Scheme:");
+        egui::ComboBox::from_id_salt("color_scheme")
+            .selected_text(hex_app.color_scheme.name())
+            .show_ui(ui, |ui| {
+                for scheme in ColorScheme::ALL {
+                    ui.selectable_value(&mut hex_app.color_scheme, scheme, scheme.name());
+                }
+            })
+            .response
+            .on_hover_text("Viridis and Cividis are colorblind-safe");
 
         ui.separator();
 
         ui.label("Color Averaging:");
         ui.checkbox(&mut hex_app.color_averaging, "Color Averaging");
+
+        ui.separator();
+
+        ui.label("Layout:");
+        ui.selectable_value(
+            &mut hex_app.main_view_layout,
+            MainViewLayout::Single,
+            "Single",
+        );
+        ui.selectable_value(
+            &mut hex_app.main_view_layout,
+            MainViewLayout::SideBySide,
+            "Side by side",
+        );
+        ui.selectable_value(
+            &mut hex_app.main_view_layout,
+            MainViewLayout::Stacked,
+            "Stacked",
+        );
+        ui.add_enabled(
+            hex_app.main_view_layout != MainViewLayout::Single,
+            egui::Checkbox::new(&mut hex_app.split_diff_overlay, "Diff overlay"),
+        );
     });
 }
+
+/// Draws a labeled combo box that selects one of the loaded files.
+fn file_combo_box(
+    hex_app: &mut HexApp,
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    label: &str,
+    mut selected_file: impl FnMut(&mut HexApp) -> &mut usize,
+) {
+    let labels: Vec<String> = hex_app
+        .files
+        .iter()
+        .enumerate()
+        .map(|(file_index, file)| file.label(file_index))
+        .collect();
+    let selected = selected_file(hex_app);
+
+    ui.label(label);
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(labels.get(*selected).cloned().unwrap_or_default())
+        .show_ui(ui, |ui| {
+            for (file_index, label) in labels.into_iter().enumerate() {
+                ui.selectable_value(selected, file_index, label);
+            }
+        });
+}
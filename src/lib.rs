@@ -1,7 +1,6 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod hex_app;
-mod range_blocks;
-mod range_border;
+mod patch;
 mod utilities;
 pub use hex_app::HexApp;
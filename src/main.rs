@@ -18,7 +18,7 @@ fn main() -> eframe::Result {
         ..Default::default()
     };
     eframe::run_native(
-        "eframe template",
+        egui_hex06::HexApp::APP_NAME,
         native_options,
         Box::new(|cc| Ok(Box::new(egui_hex06::HexApp::new(cc)))),
     )
@@ -0,0 +1,298 @@
+//! Binary patches that turn a *source* file into a *target* file.
+//!
+//! Supported formats:
+//! * IPS: the classic ROM patch format (with the truncation extension).
+//! * BPS: the beat patch format, with CRC32 checks of the source, target, and patch.
+//! * Text: a human-readable listing of `offset: old -> new` runs.
+
+mod bps;
+mod ips;
+mod text;
+
+use std::fmt;
+
+/// The supported patch formats.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PatchFormat {
+    Ips,
+    Bps,
+    Text,
+}
+
+impl PatchFormat {
+    pub const ALL: [PatchFormat; 3] = [PatchFormat::Ips, PatchFormat::Bps, PatchFormat::Text];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchFormat::Ips => "IPS",
+            PatchFormat::Bps => "BPS",
+            PatchFormat::Text => "Text",
+        }
+    }
+
+    /// The conventional file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PatchFormat::Ips => "ips",
+            PatchFormat::Bps => "bps",
+            PatchFormat::Text => "txt",
+        }
+    }
+
+    /// Guesses the format of `patch` from its header.
+    pub fn detect(patch: &[u8]) -> Self {
+        if patch.starts_with(ips::HEADER) {
+            PatchFormat::Ips
+        } else if patch.starts_with(bps::HEADER) {
+            PatchFormat::Bps
+        } else {
+            PatchFormat::Text
+        }
+    }
+
+    /// Creates a patch that turns `source` into `target`.
+    pub fn create(&self, source: &[u8], target: &[u8]) -> Result<Vec<u8>, PatchError> {
+        match self {
+            PatchFormat::Ips => ips::create(source, target),
+            PatchFormat::Bps => Ok(bps::create(source, target)),
+            PatchFormat::Text => Ok(text::create(source, target).into_bytes()),
+        }
+    }
+
+    /// Applies `patch` to `source`, returning the patched target.
+    pub fn apply(&self, patch: &[u8], source: &[u8]) -> Result<Vec<u8>, PatchError> {
+        match self {
+            PatchFormat::Ips => ips::apply(patch, source),
+            PatchFormat::Bps => bps::apply(patch, source),
+            PatchFormat::Text => {
+                let patch = std::str::from_utf8(patch).map_err(|_| PatchError::InvalidHeader)?;
+                text::apply(patch, source)
+            }
+        }
+    }
+}
+
+/// Errors from creating or applying patches.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PatchError {
+    /// The target is too large to be described in this format.
+    TooLarge,
+    /// The patch doesn't start with this format's header.
+    InvalidHeader,
+    /// The patch ended in the middle of a record.
+    UnexpectedEnd,
+    /// A BPS checksum didn't match.
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// A patch record refers to source data that doesn't exist or doesn't match.
+    SourceMismatch { offset: u64 },
+    /// A line of a text patch couldn't be parsed.
+    InvalidLine { line: usize },
+    /// The patch declares (or writes past) a target size that it can't plausibly produce.
+    InvalidTargetSize { len: u64 },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::TooLarge => write!(f, "target is too large for this patch format"),
+            PatchError::InvalidHeader => write!(f, "invalid patch header"),
+            PatchError::UnexpectedEnd => write!(f, "unexpected end of patch"),
+            PatchError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected 0x{expected:08X}, found 0x{actual:08X}"
+            ),
+            PatchError::SourceMismatch { offset } => {
+                write!(f, "source doesn't match patch at 0x{offset:08X}")
+            }
+            PatchError::InvalidLine { line } => write!(f, "invalid patch text on line {line}"),
+            PatchError::InvalidTargetSize { len } => {
+                write!(f, "invalid target size of {len} bytes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Checks a target size declared by a patch before anything is allocated for it: the target
+/// can be at most `expansion` bytes per patch byte larger than the source.
+fn check_target_len(
+    target_len: u64,
+    source: &[u8],
+    patch_len: usize,
+    expansion: u64,
+) -> Result<usize, PatchError> {
+    let limit = (patch_len as u64)
+        .saturating_mul(expansion)
+        .saturating_add(source.len() as u64);
+    if target_len > limit {
+        return Err(PatchError::InvalidTargetSize { len: target_len });
+    }
+    usize::try_from(target_len).map_err(|_| PatchError::InvalidTargetSize { len: target_len })
+}
+
+/// Runs of target bytes that differ from the source, as `(index, len)`.
+/// Target bytes past the end of the source always differ.
+fn changed_runs(source: &[u8], target: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+
+    for (i, byte) in target.iter().enumerate() {
+        if source.get(i) == Some(byte) {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((index, len)) if *index + *len == i => *len += 1,
+            _ => runs.push((i, 1)),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    /// Random source/target pair: the target is an edited copy of the source,
+    /// possibly extended or truncated.
+    fn random_pair() -> (Vec<u8>, Vec<u8>) {
+        let mut rng = rand::thread_rng();
+        let len = rng.gen_range(0..5000);
+        let source: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let mut target = source.clone();
+
+        for _ in 0..rng.gen_range(0..10) {
+            if target.is_empty() {
+                break;
+            }
+            let start = rng.gen_range(0..target.len());
+            let end = (start + rng.gen_range(1..300)).min(target.len());
+            target[start..end].iter_mut().for_each(|x| *x = rng.gen());
+        }
+        match rng.gen_range(0..3) {
+            0 => target.extend((0..rng.gen_range(1..500)).map(|_| rng.gen::<u8>())),
+            1 => target.truncate(rng.gen_range(0..=target.len())),
+            _ => {}
+        }
+
+        (source, target)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in PatchFormat::ALL {
+            for _ in 0..100 {
+                let (source, target) = random_pair();
+                let patch = format.create(&source, &target).unwrap();
+
+                assert_eq!(PatchFormat::detect(&patch), format);
+                assert_eq!(format.apply(&patch, &source).unwrap(), target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ips_eof_offset() {
+        // A record at offset 0x454F46 would be read as the "EOF" footer.
+        let source = vec![0u8; 0x454F50];
+        let mut target = source.clone();
+        target[0x454F46] = 1;
+
+        let patch = PatchFormat::Ips.create(&source, &target).unwrap();
+        assert_eq!(PatchFormat::Ips.apply(&patch, &source).unwrap(), target);
+    }
+
+    #[test]
+    fn test_bps_wrong_source() {
+        let (source, target) = (vec![1, 2, 3], vec![1, 5, 3, 4]);
+        let patch = PatchFormat::Bps.create(&source, &target).unwrap();
+
+        assert!(matches!(
+            PatchFormat::Bps.apply(&patch, &[1, 2, 4]),
+            Err(PatchError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_text_wrong_source() {
+        let (source, target) = (vec![1, 2, 3], vec![1, 5, 3, 4]);
+        let patch = PatchFormat::Text.create(&source, &target).unwrap();
+
+        assert_eq!(
+            PatchFormat::Text.apply(&patch, &[1, 6, 3]),
+            Err(PatchError::SourceMismatch { offset: 1 })
+        );
+    }
+
+    /// A BPS patch for `source` with the given header numbers and actions, and valid source
+    /// and patch checksums.
+    fn bps_patch(source: &[u8], numbers: &[u64]) -> Vec<u8> {
+        let mut patch = bps::HEADER.to_vec();
+        for &number in numbers {
+            bps::write_number(&mut patch, number);
+        }
+        patch.extend_from_slice(&bps::crc32(source).to_le_bytes());
+        patch.extend_from_slice(&0u32.to_le_bytes());
+        patch.extend_from_slice(&bps::crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn test_bps_malformed() {
+        let source = [1, 2, 3];
+        let apply = |numbers: &[u64]| PatchFormat::Bps.apply(&bps_patch(&source, numbers), &source);
+
+        // A target size out of proportion to the source and patch.
+        assert_eq!(
+            apply(&[3, 1 << 40, 0]),
+            Err(PatchError::InvalidTargetSize { len: 1 << 40 })
+        );
+        // A source read past the declared target size.
+        assert_eq!(
+            apply(&[3, 4, 0, 4 << 2]),
+            Err(PatchError::InvalidTargetSize { len: 5 })
+        );
+        // A target copy of 2^62 bytes.
+        assert_eq!(
+            apply(&[3, 4, 0, 1 << 2, ((1 << 62) - 1) << 2 | 3, 0]),
+            Err(PatchError::InvalidTargetSize { len: (1 << 62) + 2 })
+        );
+        // A source copy far past the end of the source.
+        assert_eq!(
+            apply(&[3, 4, 0, 2, 1 << 63]),
+            Err(PatchError::SourceMismatch { offset: 1 << 62 })
+        );
+    }
+
+    #[test]
+    fn test_text_malformed() {
+        let source = vec![1, 2, 3];
+        let apply = |patch: &str| PatchFormat::Text.apply(patch.as_bytes(), &source);
+
+        assert_eq!(
+            apply("size: 3 -> 1099511627775\n"),
+            Err(PatchError::InvalidTargetSize {
+                len: 0xFF_FFFF_FFFF
+            })
+        );
+        assert_eq!(
+            apply("size: 3 -> 4\n0x7FFFFFFFFF: -> 01\n"),
+            Err(PatchError::SourceMismatch {
+                offset: 0x7F_FFFF_FFFF
+            })
+        );
+        assert_eq!(
+            apply("size: 3 -> 4\n0xFFFFFFFFFFFFFFFF: 00 -> 01\n"),
+            Err(PatchError::InvalidLine { line: 2 })
+        );
+        assert_eq!(
+            apply("size: 3 -> 4\n0x00000001: 02 03 -> 05 06 07 08\n"),
+            Err(PatchError::InvalidTargetSize { len: 5 })
+        );
+        assert_eq!(
+            apply("size: 3 -> 4\n0x00000003: -> 04\n"),
+            Ok(vec![1, 2, 3, 4])
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! BPS patches: a `BPS1` header, variable-length source size, target size, and metadata,
+//! then actions that build the target, then CRC32s of the source, target, and patch.
+//!
+//! Created patches only use `SourceRead` (copy the unchanged source byte at the same offset)
+//! and `TargetRead` (literal bytes) actions; applying supports all four action types.
+
+use range_blocks::checksum::{Crc, CrcParams};
+
+use super::{changed_runs, check_target_len, PatchError};
+
+pub const HEADER: &[u8] = b"BPS1";
+/// Source, target, and patch CRC32s.
+const FOOTER_LEN: usize = 12;
+
+const SOURCE_READ: u64 = 0;
+const TARGET_READ: u64 = 1;
+const SOURCE_COPY: u64 = 2;
+const TARGET_COPY: u64 = 3;
+
+/// Limit on target bytes per patch byte beyond the source size. (`TargetCopy` actions can
+/// repeat bytes, e.g. run-length encode, so a patch can be much smaller than its target.)
+const MAX_EXPANSION: u64 = 1024;
+
+pub fn create(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = HEADER.to_vec();
+    write_number(&mut patch, source.len() as u64);
+    write_number(&mut patch, target.len() as u64);
+    write_number(&mut patch, 0); // no metadata
+
+    let write_action = |patch: &mut Vec<u8>, command: u64, len: usize| {
+        write_number(patch, ((len as u64 - 1) << 2) | command);
+    };
+
+    let mut output_offset = 0;
+    for (index, len) in changed_runs(source, target) {
+        if output_offset < index {
+            write_action(&mut patch, SOURCE_READ, index - output_offset);
+        }
+        write_action(&mut patch, TARGET_READ, len);
+        patch.extend_from_slice(&target[index..index + len]);
+        output_offset = index + len;
+    }
+    if output_offset < target.len() {
+        write_action(&mut patch, SOURCE_READ, target.len() - output_offset);
+    }
+
+    patch.extend_from_slice(&crc32(source).to_le_bytes());
+    patch.extend_from_slice(&crc32(target).to_le_bytes());
+    patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+
+    patch
+}
+
+pub fn apply(patch: &[u8], source: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if !patch.starts_with(HEADER) {
+        return Err(PatchError::InvalidHeader);
+    }
+    if patch.len() < HEADER.len() + FOOTER_LEN {
+        return Err(PatchError::UnexpectedEnd);
+    }
+
+    let (body, footer) = patch.split_at(patch.len() - FOOTER_LEN);
+    let footer_crc = |i: usize| u32::from_le_bytes(footer[4 * i..4 * i + 4].try_into().unwrap());
+    check_crc(footer_crc(2), crc32(&patch[..patch.len() - 4]))?;
+    check_crc(footer_crc(0), crc32(source))?;
+
+    let mut reader = Reader {
+        data: body,
+        offset: HEADER.len(),
+    };
+    let source_len = reader.number()?;
+    let target_len = reader.number()?;
+    let metadata_len = reader.number()?;
+    reader.bytes(metadata_len)?;
+
+    if source_len != source.len() as u64 {
+        return Err(PatchError::SourceMismatch {
+            offset: source.len() as u64,
+        });
+    }
+
+    let target_len = check_target_len(target_len, source, patch.len(), MAX_EXPANSION)?;
+    // (Not preallocated from `target_len`: the patch may not actually produce that much.)
+    let mut target = Vec::new();
+    let (mut source_offset, mut target_offset) = (0usize, 0usize);
+
+    while reader.offset < body.len() {
+        let action = reader.number()?;
+        let len = usize::try_from((action >> 2) + 1).map_err(|_| PatchError::UnexpectedEnd)?;
+        if len > target_len - target.len() {
+            return Err(PatchError::InvalidTargetSize {
+                len: (target.len() as u64).saturating_add(len as u64),
+            });
+        }
+
+        let source_range = |offset: usize| {
+            let end = offset.checked_add(len)?;
+            source.get(offset..end)
+        };
+        match action & 3 {
+            SOURCE_READ => {
+                let offset = target.len();
+                let data = source_range(offset).ok_or(PatchError::SourceMismatch {
+                    offset: offset as u64,
+                })?;
+                target.extend_from_slice(data);
+            }
+            TARGET_READ => target.extend_from_slice(reader.bytes(len as u64)?),
+            SOURCE_COPY => {
+                source_offset = reader.relative_offset(source_offset)?;
+                let data = source_range(source_offset).ok_or(PatchError::SourceMismatch {
+                    offset: source_offset as u64,
+                })?;
+                target.extend_from_slice(data);
+                source_offset += len;
+            }
+            TARGET_COPY => {
+                target_offset = reader.relative_offset(target_offset)?;
+                if target_offset >= target.len() {
+                    return Err(PatchError::UnexpectedEnd);
+                }
+                // Byte by byte: the copied range may overlap the bytes being written.
+                for _ in 0..len {
+                    let byte = target[target_offset];
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if target.len() != target_len {
+        return Err(PatchError::UnexpectedEnd);
+    }
+    check_crc(footer_crc(1), crc32(&target))?;
+
+    Ok(target)
+}
+
+fn check_crc(expected: u32, actual: u32) -> Result<(), PatchError> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(PatchError::ChecksumMismatch { expected, actual })
+    }
+}
+
+/// Writes a BPS variable-length number.
+pub(super) fn write_number(patch: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let x = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            patch.push(0x80 | x);
+            break;
+        }
+        patch.push(x);
+        value -= 1;
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: u64) -> Result<&'a [u8], PatchError> {
+        let count = usize::try_from(count).map_err(|_| PatchError::UnexpectedEnd)?;
+        let bytes = self
+            .data
+            .get(self.offset..self.offset.saturating_add(count))
+            .ok_or(PatchError::UnexpectedEnd)?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    /// Reads a BPS variable-length number.
+    fn number(&mut self) -> Result<u64, PatchError> {
+        let (mut value, mut shift) = (0u64, 1u64);
+        loop {
+            let x = self.bytes(1)?[0];
+            value = (x as u64 & 0x7F)
+                .checked_mul(shift)
+                .and_then(|x| value.checked_add(x))
+                .ok_or(PatchError::UnexpectedEnd)?;
+            if x & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_mul(0x80).ok_or(PatchError::UnexpectedEnd)?;
+            value = value.checked_add(shift).ok_or(PatchError::UnexpectedEnd)?;
+        }
+    }
+
+    /// Reads a signed relative offset and applies it to `offset`.
+    fn relative_offset(&mut self, offset: usize) -> Result<usize, PatchError> {
+        let data = self.number()?;
+        let magnitude = usize::try_from(data >> 1).map_err(|_| PatchError::UnexpectedEnd)?;
+        if data & 1 == 1 {
+            offset.checked_sub(magnitude)
+        } else {
+            offset.checked_add(magnitude)
+        }
+        .ok_or(PatchError::UnexpectedEnd)
+    }
+}
+
+/// CRC-32 (IEEE 802.3), as used by BPS.
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    Crc::new(CrcParams::CRC_32).checksum(data)
+}
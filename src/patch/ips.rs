@@ -0,0 +1,95 @@
+//! IPS patches: a `PATCH` header, then records of
+//! (3-byte big-endian offset, 2-byte big-endian size, data), then an `EOF` footer.
+//! A record with size 0 is an RLE record: a 2-byte run length and a single fill byte.
+//! An optional 3-byte length after the footer truncates the target.
+
+use super::{changed_runs, PatchError};
+
+pub const HEADER: &[u8] = b"PATCH";
+const FOOTER: &[u8] = b"EOF";
+/// A record at this offset would be indistinguishable from the footer.
+const FOOTER_OFFSET: usize = 0x454F46;
+/// Offsets are 3 bytes.
+const MAX_LEN: usize = 0x100_0000;
+/// Record sizes are 2 bytes.
+const MAX_RECORD_LEN: usize = 0xFFFF;
+
+pub fn create(source: &[u8], target: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if target.len() > MAX_LEN || source.len() > MAX_LEN {
+        return Err(PatchError::TooLarge);
+    }
+
+    let mut patch = HEADER.to_vec();
+
+    for (index, len) in changed_runs(source, target) {
+        let mut start = index;
+        let end = index + len;
+
+        while start < end {
+            let mut record_start = start;
+            if record_start == FOOTER_OFFSET {
+                // Start one byte early: rewriting an unchanged byte is harmless.
+                record_start -= 1;
+            }
+            let record_end = std::cmp::min(end, record_start + MAX_RECORD_LEN);
+
+            patch.extend_from_slice(&(record_start as u32).to_be_bytes()[1..]);
+            patch.extend_from_slice(&((record_end - record_start) as u16).to_be_bytes());
+            patch.extend_from_slice(&target[record_start..record_end]);
+
+            start = record_end;
+        }
+    }
+
+    patch.extend_from_slice(FOOTER);
+
+    if target.len() < source.len() {
+        patch.extend_from_slice(&(target.len() as u32).to_be_bytes()[1..]);
+    }
+
+    Ok(patch)
+}
+
+pub fn apply(patch: &[u8], source: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut patch = patch
+        .strip_prefix(HEADER)
+        .ok_or(PatchError::InvalidHeader)?;
+    let mut target = source.to_vec();
+
+    let mut take = |count: usize| -> Result<&[u8], PatchError> {
+        if patch.len() < count {
+            return Err(PatchError::UnexpectedEnd);
+        }
+        let (taken, rest) = patch.split_at(count);
+        patch = rest;
+        Ok(taken)
+    };
+    let be = |bytes: &[u8]| bytes.iter().fold(0, |value, &b| value << 8 | b as usize);
+
+    loop {
+        let offset = take(3)?;
+        if offset == FOOTER {
+            break;
+        }
+        let offset = be(offset);
+        let size = be(take(2)?);
+
+        let (len, data) = if size == 0 {
+            let len = be(take(2)?);
+            (len, vec![take(1)?[0]; len])
+        } else {
+            (size, take(size)?.to_vec())
+        };
+
+        if target.len() < offset + len {
+            target.resize(offset + len, 0);
+        }
+        target[offset..offset + len].copy_from_slice(&data);
+    }
+
+    if let Ok(truncate_len) = take(3) {
+        target.truncate(be(truncate_len));
+    }
+
+    Ok(target)
+}
@@ -0,0 +1,96 @@
+//! Human-readable text patches: a size line, then one line per run of changed bytes.
+//!
+//! ```text
+//! size: 1000 -> 1004
+//! 0x00000010: 41 42 -> 43 44
+//! 0x000003E6: 00 00 -> 01 02 03 04 05 06
+//! ```
+//! Old bytes are listed only where they exist in the source file.
+
+use super::{changed_runs, check_target_len, PatchError};
+
+const SIZE_PREFIX: &str = "size:";
+
+pub fn create(source: &[u8], target: &[u8]) -> String {
+    let hex = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut text = format!("{SIZE_PREFIX} {} -> {}\n", source.len(), target.len());
+
+    for (index, len) in changed_runs(source, target) {
+        let old = source
+            .get(index..source.len().min(index + len))
+            .unwrap_or(&[]);
+        let new = &target[index..index + len];
+        text += &format!("0x{index:08X}: {} -> {}\n", hex(old), hex(new));
+    }
+
+    text
+}
+
+pub fn apply(patch: &str, source: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut lines = patch.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    let (line_number, size_line) = lines.next().ok_or(PatchError::UnexpectedEnd)?;
+    let (_, target_len) = size_line
+        .strip_prefix(SIZE_PREFIX)
+        .and_then(|sizes| sizes.split_once("->"))
+        .ok_or(PatchError::InvalidHeader)?;
+    let target_len: u64 = target_len
+        .trim()
+        .parse()
+        .map_err(|_| PatchError::InvalidLine { line: line_number })?;
+    // (Every target byte past the end of the source is listed, so takes at least a patch byte.)
+    let target_len = check_target_len(target_len, source, patch.len(), 1)?;
+
+    let mut target = source.to_vec();
+
+    for (line_number, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+        let invalid = PatchError::InvalidLine { line: line_number };
+
+        let (offset, bytes) = line.split_once(':').ok_or(invalid)?;
+        let (old, new) = bytes.split_once("->").ok_or(invalid)?;
+        let offset = offset
+            .trim()
+            .strip_prefix("0x")
+            .and_then(|offset| usize::from_str_radix(offset, 16).ok())
+            .ok_or(invalid)?;
+        let (old, new) = (
+            parse_hex(old).ok_or(invalid)?,
+            parse_hex(new).ok_or(invalid)?,
+        );
+
+        let old_end = offset.checked_add(old.len()).ok_or(invalid)?;
+        if source.get(offset..old_end) != Some(old.as_slice()) {
+            return Err(PatchError::SourceMismatch {
+                offset: offset as u64,
+            });
+        }
+
+        let new_end = offset.checked_add(new.len()).ok_or(invalid)?;
+        if new_end > target_len {
+            return Err(PatchError::InvalidTargetSize {
+                len: new_end as u64,
+            });
+        }
+        if target.len() < new_end {
+            target.resize(new_end, 0);
+        }
+        target[offset..new_end].copy_from_slice(&new);
+    }
+
+    target.resize(target_len, 0);
+
+    Ok(target)
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    text.split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect()
+}